@@ -0,0 +1,135 @@
+#!/usr/bin/env python3
+"""
+IdeaArchitect 命令行工具
+不启动界面，直接对想法执行 澄清 -> 总结 -> 报告 流程，便于脚本与 CI 使用
+
+示例：
+    python cli.py idea.txt -o report.md
+    echo "一个帮助学生复习的应用" | python cli.py - --format json
+    python cli.py idea.txt --interactive
+    python cli.py idea.txt --answers answers.json --provider openai --model gpt-4o-mini
+    python cli.py https://example.com/pitch.html -o report.md
+    python cli.py proposal.pdf --format html -o report.html
+"""
+
+import argparse
+import json
+import logging
+import sys
+from pathlib import Path
+
+# 添加项目根目录到Python路径
+project_root = Path(__file__).parent
+sys.path.insert(0, str(project_root))
+
+from config.app_config import ensure_directories
+from src.utils.report import EXPORT_FORMATS
+
+
+def parse_args(argv=None) -> argparse.Namespace:
+    parser = argparse.ArgumentParser(description="IdeaArchitect 命令行：澄清想法并输出总结报告")
+    parser.add_argument("idea", help="想法来源：文本 / html / pdf / docx 文件路径或网页 URL，使用 - 从标准输入读取")
+    parser.add_argument("-o", "--output", help="报告输出路径（默认输出到标准输出；PDF 必须指定）")
+    parser.add_argument("-f", "--format", default="markdown", choices=sorted(EXPORT_FORMATS), help="报告格式")
+    parser.add_argument("--json", action="store_true", help="等同于 --format json")
+    parser.add_argument("--domain", help="想法所属领域")
+    parser.add_argument("--answers", help="预先准备的回答（JSON：{slot_name: answer}）")
+    parser.add_argument("--interactive", action="store_true", help="在终端中逐个回答澄清问题")
+    parser.add_argument("--provider", help="只使用指定的提供商（openai/anthropic/deepseek/qwen/gemini，mock 为不联网的模拟提供商）")
+    parser.add_argument("--model", help="覆盖所选提供商的模型（需配合 --provider）")
+    parser.add_argument("-q", "--quiet", action="store_true", help="只输出警告及以上级别的日志")
+    return parser.parse_args(argv)
+
+
+def redirect_console_logs(quiet: bool) -> None:
+    """控制台日志改写到标准错误，避免混入标准输出的报告"""
+    for handler in logging.getLogger().handlers:
+        if type(handler) is logging.StreamHandler:
+            handler.setStream(sys.stderr)
+            if quiet:
+                handler.setLevel(logging.WARNING)
+
+
+def ask(question: dict) -> str:
+    print(f"\n{question['question']}", file=sys.stderr)
+    return input("> ").strip()
+
+
+def main(argv=None) -> int:
+    args = parse_args(argv)
+    fmt = "json" if args.json else args.format
+    if fmt == "pdf" and not args.output:
+        print("PDF 报告需要通过 -o 指定输出文件", file=sys.stderr)
+        return 2
+
+    ensure_directories()
+    from src.api import questioning_api
+    from src.api.export_api import api_export_result
+    from src.utils.providers import PROVIDER_SETTINGS
+    from src.utils.report import build_report, render_report
+    redirect_console_logs(args.quiet)
+
+    model_overrides = None
+    if args.provider:
+        if args.provider not in PROVIDER_SETTINGS:
+            print(f"不支持的提供商: {args.provider}", file=sys.stderr)
+            return 2
+        model_overrides = {"providers": [args.provider]}
+        if args.model:
+            model_overrides["models"] = {args.provider: args.model}
+    elif args.model:
+        print("--model 需要配合 --provider 使用", file=sys.stderr)
+        return 2
+
+    # 在创建会话之前读取回答文件，文件有误时不留下半成品会话
+    answers = {}
+    if args.answers:
+        try:
+            answers = json.loads(Path(args.answers).read_text(encoding="utf-8"))
+        except (OSError, ValueError) as e:
+            print(f"无法读取回答文件 {args.answers}: {e}", file=sys.stderr)
+            return 2
+        if not isinstance(answers, dict):
+            print(f"回答文件 {args.answers} 应为 JSON 对象（槽位名 -> 回答）", file=sys.stderr)
+            return 2
+
+    if args.idea == "-":
+        seed = {"raw_text": sys.stdin.read().strip(), "context_hints": []}
+    else:
+        seed = {"source": args.idea, "context_hints": []}
+    if args.domain:
+        seed["domain"] = args.domain
+    started = questioning_api.api_start_clarification_session(seed, model_overrides)
+    if not started.get("success"):
+        print(f"创建澄清会话失败: {started.get('error')}", file=sys.stderr)
+        return 1
+    session_id = started["session_id"]
+
+    for question in started["questions"]:
+        answer = answers.get(question["slot_name"])
+        if not answer and args.interactive:
+            answer = ask(question)
+        if answer:
+            questioning_api.api_submit_clarification_answer(session_id, question["slot_name"], str(answer))
+
+    regenerated = questioning_api.api_regenerate_summary(session_id)
+    if not regenerated.get("success"):
+        print(f"生成总结失败: {regenerated.get('error')}", file=sys.stderr)
+        return 1
+    for warning in [*started.get("warnings", []), *regenerated.get("warnings", [])]:
+        print(f"警告: {warning['message']}", file=sys.stderr)
+
+    if args.output:
+        exported = api_export_result(session_id, fmt, args.output)
+        if not exported.get("success"):
+            print(exported.get("error"), file=sys.stderr)
+            return 1
+        print(exported["data"]["path"], file=sys.stderr)
+    else:
+        session = questioning_api.ClarificationSessionManager().load(session_id)
+        sys.stdout.write(render_report(build_report(session), fmt) + "\n")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
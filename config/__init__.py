@@ -2,6 +2,6 @@
 Configuration Package
 """
 
-from .app_config import app_config, ensure_directories, ModelConfig
+from .app_config import app_config, ensure_directories, get_storage_paths, ModelConfig
 
-__all__ = ["app_config", "ensure_directories", "ModelConfig"]
+__all__ = ["app_config", "ensure_directories", "get_storage_paths", "ModelConfig"]
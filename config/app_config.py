@@ -2,12 +2,20 @@
 IdeaArchitect Application Configuration
 """
 
+import json
+import logging
 import os
+import shutil
+import sys
+import tempfile
 from pathlib import Path
-from typing import Dict, Any, Optional
-from pydantic import BaseModel, Field
+from typing import Dict, Any, List, Literal, Optional
+from pydantic import BaseModel, Field, PrivateAttr, TypeAdapter
 from enum import Enum
 
+# 使用标准库 logging（src/utils/logger 依赖本模块）；不能 print，MCP 服务器的 stdout 只用于 JSON-RPC
+logger = logging.getLogger(__name__)
+
 
 class LogLevel(str, Enum):
     DEBUG = "DEBUG"
@@ -22,6 +30,136 @@ class WorkflowMode(str, Enum):
     RIGOROUS = "rigorous"
 
 
+APP_DIR_NAME = "IdeaArchitect"
+
+# 持久化配置文件
+CONFIG_FILE_NAME = "settings.json"
+CONFIG_SCHEMA_VERSION = 1
+
+# 允许用户修改并写入配置文件的字段
+PERSISTED_FIELDS = [
+    "openai_api_key",
+    "anthropic_api_key",
+    "deepseek_api_key",
+    "qwen_api_key",
+    "gemini_api_key",
+    "default_model_provider",
+    "default_model_name",
+    "provider_order",
+    "offline",
+    "output_language",
+    "safety_filter_enabled",
+    "moderation_enabled",
+    "redact_sensitive_data",
+    "enable_streaming",
+    "agent_settings",
+    "model_endpoints",
+    "default_workflow_mode",
+    "max_discussion_rounds",
+    "convergence_threshold",
+    "max_concurrent_agents",
+    "max_concurrent_workflows",
+    "agent_timeout",
+    "request_timeout",
+    "max_retries",
+    "retry_delay",
+    "provider_rate_limits",
+    "response_cache_enabled",
+    "response_cache_ttl_hours",
+    "cache_max_size_mb",
+    "health_check_interval",
+    "provider_check_interval",
+    "audit_log_enabled",
+    "audit_max_size_mb",
+    "audit_backup_count",
+    "log_level",
+    "log_format",
+    "log_max_size_mb",
+    "log_backup_count",
+    "log_redact_prompts",
+]
+
+# 配置文件中的密钥字段与提供商环境变量的对应关系
+API_KEY_ENV_NAMES = {
+    "openai_api_key": "OPENAI_API_KEY",
+    "anthropic_api_key": "ANTHROPIC_API_KEY",
+    "deepseek_api_key": "DEEPSEEK_API_KEY",
+    "qwen_api_key": "QWEN_API_KEY",
+    "gemini_api_key": "GEMINI_API_KEY",
+}
+
+# 旧版本在项目根目录下使用的相对数据目录
+LEGACY_DATA_DIR = Path(__file__).resolve().parent.parent / "data"
+
+
+def platform_data_root() -> Path:
+    """平台约定的应用数据目录"""
+    if sys.platform == "win32":
+        return Path(os.getenv("APPDATA") or Path.home() / "AppData" / "Roaming") / APP_DIR_NAME
+    if sys.platform == "darwin":
+        return Path.home() / "Library" / "Application Support" / APP_DIR_NAME
+    return Path(os.getenv("XDG_DATA_HOME") or Path.home() / ".local" / "share") / APP_DIR_NAME.lower()
+
+
+def platform_config_root() -> Path:
+    """平台约定的应用配置目录"""
+    if sys.platform in ("win32", "darwin"):
+        return platform_data_root()
+    return Path(os.getenv("XDG_CONFIG_HOME") or Path.home() / ".config") / APP_DIR_NAME.lower()
+
+
+def platform_cache_root() -> Path:
+    """平台约定的应用缓存目录"""
+    if sys.platform == "win32":
+        return Path(os.getenv("LOCALAPPDATA") or Path.home() / "AppData" / "Local") / APP_DIR_NAME / "Cache"
+    if sys.platform == "darwin":
+        return Path.home() / "Library" / "Caches" / APP_DIR_NAME
+    return Path(os.getenv("XDG_CACHE_HOME") or Path.home() / ".cache") / APP_DIR_NAME.lower()
+
+
+class AgentGenerationConfig(BaseModel):
+    """单个智能体的生成参数"""
+    # 按提供商指定模型，例如 {"openai": "gpt-4o-mini"}；未指定时使用提供商默认模型
+    models: Dict[str, str] = Field(default_factory=dict)
+    temperature: float = 0.3
+    max_tokens: Optional[int] = None
+    top_p: Optional[float] = None
+    # 非空时替代 prompts 模板中的系统提示词
+    system_prompt: str = ""
+    # 是否使用模型响应缓存（需同时开启 response_cache_enabled）
+    cache: bool = True
+    # 单次请求超时（秒）；未设置时使用 request_timeout
+    timeout: Optional[float] = None
+    # 所有提供商都失败时的处理：使用内置规则（fallback_to_rules）、直接报错（fail_fast）或跳过该步骤并给出警告（skip_with_warning）
+    degradation: Literal["fallback_to_rules", "fail_fast", "skip_with_warning"] = "fallback_to_rules"
+
+
+class ModelEndpointConfig(BaseModel):
+    """按模型覆盖请求地址（Azure OpenAI 部署、自建 OpenAI 兼容网关等）"""
+    # 例如 https://xxx.openai.azure.com 或 https://xxx.openai.azure.com/openai/deployments/gpt-4o
+    base_url: str
+    # 未设置时使用提供商的密钥
+    api_key: Optional[str] = None
+    # 作为 api-version 查询参数附加；Azure 地址未设置时使用 AZURE_DEFAULT_API_VERSION
+    api_version: Optional[str] = None
+
+
+AZURE_DEFAULT_API_VERSION = "2024-06-01"
+
+
+class ProviderRateLimit(BaseModel):
+    """单个提供商的请求限额（0 表示不限制）"""
+    requests_per_minute: int = 0
+    # 按提示词字符数 / 4 加 max_tokens 估算
+    tokens_per_minute: int = 0
+
+
+DEFAULT_AGENT_SETTINGS: Dict[str, AgentGenerationConfig] = {
+    "clarifier": AgentGenerationConfig(max_tokens=800),
+    "summarizer": AgentGenerationConfig(max_tokens=1200, timeout=60),
+}
+
+
 class AppConfig(BaseModel):
     """应用程序配置"""
 
@@ -35,8 +173,10 @@ class AppConfig(BaseModel):
     eel_host: str = "localhost"
     eel_size: tuple = (1200, 800)
 
-    # 数据存储配置
+    # 数据存储配置（按工作区划分子目录，默认位于平台数据目录下）
+    workspace: str = "default"
     data_dir: Path = Path("data")
+    config_dir: Path = Path("data")
     projects_dir: Path = Path("data/projects")
     cache_dir: Path = Path("data/cache")
     logs_dir: Path = Path("data/logs")
@@ -44,14 +184,40 @@ class AppConfig(BaseModel):
     # 日志配置
     log_level: LogLevel = LogLevel.INFO
     log_file: str = "ideaarchitect.log"
+    # 文件日志格式：text 或 json（每行一个 JSON 对象）
+    log_format: Literal["text", "json"] = "text"
+    # 单个日志文件上限（MB）与保留的历史文件数
+    log_max_size_mb: float = 10.0
+    log_backup_count: int = 5
+    # 调试日志中只记录提示词长度与摘要，不记录原文
+    log_redact_prompts: bool = True
 
     # AI模型配置
     openai_api_key: Optional[str] = None
     anthropic_api_key: Optional[str] = None
     deepseek_api_key: Optional[str] = None
     qwen_api_key: Optional[str] = None
+    gemini_api_key: Optional[str] = None
     default_model_provider: str = "openai"
     default_model_name: str = "gpt-4"
+    # 澄清与总结依次尝试的提供商：前一个失败（限流、超时、服务端错误等）时回退到下一个，未配置密钥的跳过
+    provider_order: List[str] = Field(default_factory=lambda: ["qwen", "deepseek", "openai", "anthropic", "gemini"])
+    # 离线模式：不发起任何模型请求，全部使用内置的规则化问题与总结
+    offline: bool = False
+    # 智能体输出与报告的语言（zh-CN / en-US / ja-JP），与界面语言无关；未设置时跟随想法原文的语言
+    output_language: Optional[Literal["zh-CN", "en-US", "ja-JP"]] = None
+    # 写入提示词前清理想法文本（遮盖密钥、移除改写系统提示词的指令）；可选调用 OpenAI 内容审核并记录结果
+    safety_filter_enabled: bool = True
+    moderation_enabled: bool = False
+    # 日志、模型响应缓存与导出内容中遮盖密钥、邮箱与会话保密词
+    redact_sensitive_data: bool = True
+    # 智能体以流式请求获取输出，并通过 agent.output_chunk 事件推送到界面逐步显示
+    enable_streaming: bool = False
+    agent_settings: Dict[str, AgentGenerationConfig] = Field(
+        default_factory=lambda: {name: cfg.model_copy(deep=True) for name, cfg in DEFAULT_AGENT_SETTINGS.items()}
+    )
+    # 模型名 -> 请求地址覆盖
+    model_endpoints: Dict[str, ModelEndpointConfig] = Field(default_factory=dict)
 
     # 工作流配置
     default_workflow_mode: WorkflowMode = WorkflowMode.BALANCED
@@ -60,15 +226,63 @@ class AppConfig(BaseModel):
 
     # 智能体配置
     max_concurrent_agents: int = 5
-    agent_timeout: int = 300  # 5分钟
+    max_concurrent_workflows: int = 2  # 同时运行的工作流数，超出的进入队列（重启后生效）
+    agent_timeout: int = 300  # 5分钟，单个智能体步骤（含重试与提供商回退）的总时限
 
     # 网络配置
     request_timeout: int = 30
     max_retries: int = 3
     retry_delay: float = 1.0
+    # 提供商 -> 每分钟请求数 / token 数上限，超出时排队，不同会话轮流放行
+    provider_rate_limits: Dict[str, ProviderRateLimit] = Field(default_factory=dict)
+
+    # 模型响应缓存（相同模型、提示词与参数在有效期内直接复用结果）
+    response_cache_enabled: bool = True
+    response_cache_ttl_hours: float = 24.0
+    # 缓存目录容量上限（MB，0 表示不限制）与后台清理间隔（秒）
+    cache_max_size_mb: float = 200.0
+    cache_janitor_interval: int = 3600
+    # 后台健康检查间隔（秒，0 表示不启动），检查数据、缓存与日志目录是否可写
+    health_check_interval: int = 300
+    # 后台定期检查提供商（请求免费的模型列表接口）的间隔（秒）；默认 0 不检查，手动刷新健康状态时仍会检查
+    provider_check_interval: int = 0
+    # 审计日志：记录接口调用、配置与密钥修改（遮盖）、智能体执行，写入数据目录下的 audit.jsonl
+    audit_log_enabled: bool = True
+    # 审计日志单个文件上限（MB）与保留的滚动份数
+    audit_max_size_mb: float = 10.0
+    audit_backup_count: int = 3
+
+    # 模型请求录制与回放（用于确定性测试）：off / record / replay；夹具目录默认为数据目录下的 recordings
+    vcr_mode: Literal["off", "record", "replay"] = "off"
+    vcr_dir: Path = Path("data/recordings")
+    # 模拟提供商的响应夹具目录（<智能体>.json）；未设置时使用内置响应
+    mock_fixtures_dir: Optional[Path] = None
+
+    # 来自配置文件或用户修改的设置；只有这些会被写回文件，环境变量中的值不会落盘
+    _saved_settings: Dict[str, Any] = PrivateAttr(default_factory=dict)
 
     def __init__(self, **kwargs):
         super().__init__(**kwargs)
+        # 存储位置：构造时显式传入的优先，其次环境变量，否则使用平台目录下的工作区子目录
+        explicit = set(self.model_fields_set)
+
+        def _location(field_name: str, env_name: str, default: Path) -> Path:
+            if field_name in explicit:
+                return Path(getattr(self, field_name))
+            return Path(os.getenv(env_name) or default)
+
+        if "workspace" not in explicit:
+            self.workspace = os.getenv("IDEAARCHITECT_WORKSPACE", self.workspace)
+        self.data_dir = _location("data_dir", "DATA_DIR", platform_data_root() / "workspaces" / self.workspace)
+        self.config_dir = _location("config_dir", "CONFIG_DIR", platform_config_root())
+        self.projects_dir = _location("projects_dir", "PROJECTS_DIR", self.data_dir / "projects")
+        self.cache_dir = _location("cache_dir", "CACHE_DIR", platform_cache_root() / "workspaces" / self.workspace)
+        self.logs_dir = _location("logs_dir", "LOGS_DIR", self.data_dir / "logs")
+        self.vcr_dir = _location("vcr_dir", "IDEAARCHITECT_VCR_DIR", self.data_dir / "recordings")
+        if os.getenv("IDEAARCHITECT_MOCK_FIXTURES"):
+            self.mock_fixtures_dir = Path(os.environ["IDEAARCHITECT_MOCK_FIXTURES"])
+        # 先加载配置文件，环境变量再覆盖
+        self.load_settings()
         # 从环境变量加载配置
         self.eel_port = int(os.getenv("EEL_PORT", self.eel_port))
         self.eel_host = os.getenv("EEL_HOST", self.eel_host)
@@ -78,7 +292,92 @@ class AppConfig(BaseModel):
         self.deepseek_api_key = os.getenv("DEEPSEEK_API_KEY", self.deepseek_api_key)
         # Qwen 同时兼容 DASHSCOPE_API_KEY 与 QWEN_API_KEY
         self.qwen_api_key = os.getenv("QWEN_API_KEY", os.getenv("DASHSCOPE_API_KEY", self.qwen_api_key))
-        self.debug = os.getenv("DEBUG", "false").lower() == "true"
+        # Gemini 同时兼容 GEMINI_API_KEY 与 GOOGLE_API_KEY
+        self.gemini_api_key = os.getenv("GEMINI_API_KEY", os.getenv("GOOGLE_API_KEY", self.gemini_api_key))
+        if os.getenv("DEBUG") is not None:
+            self.debug = os.getenv("DEBUG", "false").lower() == "true"
+        if os.getenv("IDEAARCHITECT_OFFLINE") is not None:
+            self.offline = os.getenv("IDEAARCHITECT_OFFLINE", "false").lower() in ("1", "true", "yes")
+        if os.getenv("IDEAARCHITECT_VCR_MODE", "").lower() in ("off", "record", "replay"):
+            self.vcr_mode = os.environ["IDEAARCHITECT_VCR_MODE"].lower()
+        if os.getenv("MODEL_PROVIDER"):
+            self.default_model_provider = os.environ["MODEL_PROVIDER"]
+        if os.getenv("OUTPUT_LANGUAGE"):
+            self.output_language = os.environ["OUTPUT_LANGUAGE"]
+        if os.getenv("LOG_LEVEL"):
+            self.log_level = LogLevel(os.environ["LOG_LEVEL"].upper())
+        if os.getenv("LOG_FORMAT", "").lower() in ("text", "json"):
+            self.log_format = os.environ["LOG_FORMAT"].lower()
+        # 提供商请求直接读取环境变量，这里把配置文件中的密钥补充进去
+        for field_name, env_name in API_KEY_ENV_NAMES.items():
+            value = getattr(self, field_name)
+            if value and not os.getenv(env_name):
+                os.environ[env_name] = value
+
+    @property
+    def settings_file(self) -> Path:
+        return self.config_dir / CONFIG_FILE_NAME
+
+    def load_settings(self) -> None:
+        """从配置文件加载用户设置；文件不存在或版本过新时保持默认值"""
+        if not self.settings_file.exists():
+            return
+        try:
+            with open(self.settings_file, 'r', encoding='utf-8') as f:
+                data = json.load(f)
+            version = data.get("schema_version", 0)
+            if version > CONFIG_SCHEMA_VERSION:
+                logger.warning(f"配置文件版本 {version} 高于当前支持的版本 {CONFIG_SCHEMA_VERSION}，已忽略")
+                return
+            self.apply_settings(data.get("settings", {}))
+        except Exception as e:
+            logger.warning(f"加载配置文件失败，使用默认配置: {e}")
+
+    def apply_settings(self, settings: Dict[str, Any]) -> None:
+        """校验并应用设置，未知字段会抛出 ValueError"""
+        unknown = [name for name in settings if name not in PERSISTED_FIELDS]
+        if unknown:
+            raise ValueError(f"不支持的配置项: {', '.join(unknown)}")
+        validated = {
+            name: TypeAdapter(type(self).model_fields[name].annotation).validate_python(value)
+            for name, value in settings.items()
+        }
+        for name, value in validated.items():
+            setattr(self, name, value)
+        self._saved_settings.update(validated)
+
+    def get_agent_settings(self, agent: str) -> AgentGenerationConfig:
+        """获取智能体生成参数；未配置的字段回落到内置默认值"""
+        default = DEFAULT_AGENT_SETTINGS.get(agent, AgentGenerationConfig())
+        configured = self.agent_settings.get(agent)
+        if not configured:
+            return default
+        return default.model_copy(update=configured.model_dump(exclude_unset=True))
+
+    def save_settings(self) -> Path:
+        """原子写入用户设置（先写临时文件再替换）
+
+        密钥以明文保存在文件中，写入后把文件权限设为仅当前用户可读写。
+        """
+        self.config_dir.mkdir(parents=True, exist_ok=True)
+        payload = {
+            "schema_version": CONFIG_SCHEMA_VERSION,
+            "settings": {
+                name: TypeAdapter(type(self).model_fields[name].annotation).dump_python(value, mode="json", exclude_unset=True)
+                for name, value in self._saved_settings.items()
+            },
+        }
+        fd, tmp_path = tempfile.mkstemp(dir=self.config_dir, prefix=".settings.", suffix=".tmp")
+        try:
+            with os.fdopen(fd, 'w', encoding='utf-8') as f:
+                json.dump(payload, f, ensure_ascii=False, indent=2)
+            os.replace(tmp_path, self.settings_file)
+            os.chmod(self.settings_file, 0o600)
+        except Exception:
+            if os.path.exists(tmp_path):
+                os.unlink(tmp_path)
+            raise
+        return self.settings_file
 
 
 class ModelConfig:
@@ -95,6 +394,11 @@ class ModelConfig:
             "api_base": "https://api.anthropic.com",
             "supports_streaming": True
         },
+        "gemini": {
+            "models": ["gemini-1.5-pro", "gemini-1.5-flash"],
+            "api_base": "https://generativelanguage.googleapis.com/v1beta",
+            "supports_streaming": True
+        },
         "ollama": {
             "models": ["llama2", "codellama", "mistral"],
             "api_base": "http://localhost:11434",
@@ -129,6 +433,7 @@ def ensure_directories():
     """确保必要的目录存在"""
     directories = [
         app_config.data_dir,
+        app_config.config_dir,
         app_config.projects_dir,
         app_config.cache_dir,
         app_config.logs_dir
@@ -137,6 +442,44 @@ def ensure_directories():
     for directory in directories:
         directory.mkdir(parents=True, exist_ok=True)
 
+    migrate_legacy_data()
+
+
+def migrate_legacy_data() -> int:
+    """将旧版项目根目录 data/ 下的项目与会话复制到当前数据目录（仅复制目标中不存在的文件）"""
+    if not LEGACY_DATA_DIR.exists() or LEGACY_DATA_DIR.resolve() == app_config.data_dir.resolve():
+        return 0
+
+    migrated = 0
+    targets = {
+        "projects": app_config.projects_dir,
+        "sessions": app_config.data_dir / "sessions",
+    }
+    for name, target_dir in targets.items():
+        source_dir = LEGACY_DATA_DIR / name
+        if not source_dir.is_dir():
+            continue
+        target_dir.mkdir(parents=True, exist_ok=True)
+        for source_file in source_dir.glob("*.json"):
+            target_file = target_dir / source_file.name
+            if not target_file.exists():
+                shutil.copy2(source_file, target_file)
+                migrated += 1
+    return migrated
+
+
+def get_storage_paths() -> Dict[str, str]:
+    """返回各类数据的存储位置"""
+    return {
+        "workspace": app_config.workspace,
+        "data_dir": str(app_config.data_dir.resolve()),
+        "config_dir": str(app_config.config_dir.resolve()),
+        "projects_dir": str(app_config.projects_dir.resolve()),
+        "sessions_dir": str((app_config.data_dir / "sessions").resolve()),
+        "cache_dir": str(app_config.cache_dir.resolve()),
+        "logs_dir": str(app_config.logs_dir.resolve()),
+    }
+
 
 def get_model_config(provider: str, model: str, mode: WorkflowMode) -> Dict[str, Any]:
     """获取模型配置"""
@@ -18,6 +18,9 @@ import eel
 from config.app_config import app_config, ensure_directories
 from src.utils.logger import get_logger, setup_logging
 from src.utils.event_bus import event_bus, EventTypes
+from src.utils.storage import start_cache_janitor
+from src.utils.health import start_health_checker
+from src.utils.audit import audited, is_read_only_command
 from src.api import *
 # 为了类型检查与静态分析友好，显式导入关键API符号
 try:
@@ -52,47 +55,94 @@ def setup_eel():
     logger.info(f"Eel框架初始化完成，前端目录: {web_dir}")
 
 
+def expose(func):
+    """暴露给前端的接口；除只读与轮询类接口外，每次调用记录到审计日志"""
+    eel.expose(func if is_read_only_command(func.__name__) else audited(func))
+
+
 def register_api_endpoints():
     """注册API端点"""
     
     # 项目管理API
-    eel.expose(api_create_project)
-    eel.expose(api_load_project)
-    eel.expose(api_save_project)
-    eel.expose(api_list_projects)
-    eel.expose(api_delete_project)
+    expose(api_create_project)
+    expose(api_load_project)
+    expose(api_save_project)
+    expose(api_list_projects)
+    expose(api_delete_project)
     
     # 工作流API
-    eel.expose(api_start_workflow)
-    eel.expose(api_get_workflow_status)
-    eel.expose(api_pause_workflow)
-    eel.expose(api_resume_workflow)
-    eel.expose(api_stop_workflow)
+    expose(api_start_workflow)
+    expose(api_get_workflow_status)
+    expose(api_get_session_progress)
+    expose(api_list_running_jobs)
+    expose(api_pause_workflow)
+    expose(api_resume_workflow)
+    expose(api_stop_workflow)
+    expose(api_get_session_trace)
+    expose(api_export_session_trace)
     
     # 智能体API
-    eel.expose(api_list_agents)
-    eel.expose(api_create_agent)
-    eel.expose(api_get_agent_status)
-    eel.expose(api_configure_agent)
+    expose(api_list_agents)
+    expose(api_create_agent)
+    expose(api_get_agent_status)
+    expose(api_configure_agent)
     
     # 模型API
-    eel.expose(api_call_ai_model)
-    eel.expose(api_test_model_connection)
-    eel.expose(api_list_available_models)
-    eel.expose(api_get_model_config)
+    expose(api_call_ai_model)
+    expose(api_test_model_connection)
+    expose(api_test_ai_connection)
+    expose(api_validate_api_key)
+    expose(api_list_available_models)
+    expose(api_get_model_config)
+
+    # 导出API
+    expose(api_export_result)
+    expose(api_export_session_bundle)
+    expose(api_import_session_bundle)
+
+    # 系统API
+    expose(api_get_storage_paths)
+    expose(api_open_data_directory)
+    expose(api_save_config)
+    expose(api_get_config)
+    expose(api_get_cache_stats)
+    expose(api_get_rate_limit_stats)
+    expose(api_get_system_health)
+    expose(api_get_audit_log)
+    expose(api_clear_cache)
 
     # 澄清/智能问答 API
     try:
-        eel.expose(run_clarification_ai)
+        expose(run_clarification_ai)
     except NameError:
         # 兼容未导入情况
         pass
     # 新的澄清会话式API
     try:
-        eel.expose(api_start_clarification_session)
-        eel.expose(api_submit_clarification_answer)
-        eel.expose(api_get_clarification_status)
-        eel.expose(api_finish_clarification)
+        expose(api_start_clarification_session)
+        expose(api_submit_clarification_answer)
+        expose(api_get_clarification_status)
+        expose(api_finish_clarification)
+        expose(api_submit_summary)
+        expose(api_list_sessions)
+        expose(api_search_sessions)
+        expose(api_set_session_tags)
+        expose(api_set_session_folder)
+        expose(api_list_tags)
+        expose(api_delete_session)
+        expose(api_rename_session)
+        expose(api_duplicate_session)
+        expose(api_set_confidential_terms)
+        expose(api_get_confidential_terms)
+        expose(api_recover_session)
+        expose(api_list_domain_templates)
+        expose(api_list_prompts)
+        expose(api_reload_prompts)
+        expose(api_get_summary)
+        expose(api_regenerate_summary)
+        expose(api_update_summary)
+        expose(api_rollback_summary)
+        expose(api_get_analytics)
     except NameError:
         pass
     
@@ -150,8 +200,19 @@ def check_dependencies():
         # 检查必要的目录
         ensure_directories()
         
+        # 定期清理过期与超限的缓存
+        start_cache_janitor()
+        
+        # 定期检查模型提供商与存储目录，供界面显示状态
+        start_health_checker()
+        
+        # 上次异常退出时仍在生成问题的澄清会话标记为已中断，可通过 recover_session 恢复
+        recover_interrupted_sessions()
+        
         # 检查AI模型配置（任一可用即可）
-        if not any([
+        if app_config.offline:
+            logger.info("离线模式：不连接模型提供商，使用内置规则生成问题与总结")
+        elif not any([
             app_config.openai_api_key,
             app_config.anthropic_api_key,
             getattr(app_config, 'deepseek_api_key', None),
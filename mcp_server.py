@@ -0,0 +1,171 @@
+#!/usr/bin/env python3
+"""
+IdeaArchitect MCP 服务器
+通过 Model Context Protocol（stdio，逐行 JSON-RPC 2.0）向外部智能体宿主（Claude Desktop、IDE 等）
+暴露想法澄清流程：clarify_idea、iterate_idea、get_report
+
+配置示例（Claude Desktop claude_desktop_config.json）：
+    {"mcpServers": {"ideaarchitect": {"command": "python", "args": ["<项目路径>/mcp_server.py"]}}}
+"""
+
+import json
+import logging
+import sys
+from pathlib import Path
+from typing import Dict, Any, Optional
+
+# 添加项目根目录到Python路径
+project_root = Path(__file__).parent
+sys.path.insert(0, str(project_root))
+
+from config.app_config import app_config, ensure_directories
+
+PROTOCOL_VERSION = "2024-11-05"
+
+TOOLS = [
+    {
+        "name": "clarify_idea",
+        "description": "为想法创建澄清会话，返回 session_id 与需要回答的澄清问题",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "idea": {"type": "string", "description": "想法原文"},
+                "domain": {"type": "string", "description": "所属领域（可选）"},
+            },
+            "required": ["idea"],
+        },
+    },
+    {
+        "name": "iterate_idea",
+        "description": "提交澄清问题的回答（slot_name -> answer）并重新生成总结",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "session_id": {"type": "string"},
+                "answers": {"type": "object", "additionalProperties": {"type": "string"}},
+            },
+            "required": ["session_id"],
+        },
+    },
+    {
+        "name": "get_report",
+        "description": "获取会话报告（markdown / json / html）",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "session_id": {"type": "string"},
+                "format": {"type": "string", "enum": ["markdown", "json", "html"]},
+            },
+            "required": ["session_id"],
+        },
+    },
+]
+
+
+def _text(data: Any) -> Dict[str, Any]:
+    text = data if isinstance(data, str) else json.dumps(data, ensure_ascii=False, indent=2)
+    return {"content": [{"type": "text", "text": text}], "isError": False}
+
+
+def _error(message: str) -> Dict[str, Any]:
+    return {"content": [{"type": "text", "text": message}], "isError": True}
+
+
+def call_tool(name: str, arguments: Dict[str, Any]) -> Dict[str, Any]:
+    from src.api import questioning_api
+    from src.utils.report import build_report, render_report
+
+    if name == "clarify_idea":
+        seed = {"raw_text": arguments.get("idea", ""), "context_hints": []}
+        if arguments.get("domain"):
+            seed["domain"] = arguments["domain"]
+        result = questioning_api.api_start_clarification_session(seed)
+        if not result.get("success"):
+            return _error(result.get("error", "clarify failed"))
+        return _text({
+            "session_id": result["session_id"],
+            "questions": [{"slot_name": q["slot_name"], "question": q["question"]} for q in result["questions"]],
+        })
+
+    if name == "iterate_idea":
+        session_id = arguments.get("session_id", "")
+        for slot_name, answer in (arguments.get("answers") or {}).items():
+            submitted = questioning_api.api_submit_clarification_answer(session_id, slot_name, str(answer))
+            if not submitted.get("success"):
+                return _error(submitted.get("error", "submit answer failed"))
+        result = questioning_api.api_regenerate_summary(session_id)
+        if not result.get("success"):
+            return _error(result.get("error", "summary failed"))
+        return _text(result["data"])
+
+    if name == "get_report":
+        session = questioning_api.ClarificationSessionManager().load(arguments.get("session_id", ""))
+        if not session:
+            return _error("session not found")
+        fmt = arguments.get("format") or "markdown"
+        if fmt not in ("markdown", "json", "html"):
+            return _error(f"unsupported format: {fmt}")
+        return _text(render_report(build_report(session), fmt))
+
+    return _error(f"unknown tool: {name}")
+
+
+def handle(message: Dict[str, Any]) -> Optional[Dict[str, Any]]:
+    """处理一条 JSON-RPC 消息；通知（无 id）不返回响应"""
+    method = message.get("method")
+    msg_id = message.get("id")
+    params = message.get("params") or {}
+    if msg_id is None:
+        return None
+
+    if method == "initialize":
+        result = {
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {"tools": {}},
+            "serverInfo": {"name": "ideaarchitect", "version": app_config.app_version},
+        }
+    elif method == "ping":
+        result = {}
+    elif method == "tools/list":
+        result = {"tools": TOOLS}
+    elif method == "tools/call":
+        from src.utils.audit import record_audit, summarize_value
+        name, arguments = params.get("name", ""), params.get("arguments") or {}
+        try:
+            result = call_tool(name, arguments)
+        except Exception as e:
+            result = _error(str(e))
+        session_id = arguments.get("session_id")
+        record_audit("command", f"mcp.{name}", success=not result.get("isError"),
+                     session_id=session_id if isinstance(session_id, str) else None, arguments=summarize_value(arguments))
+    else:
+        return {"jsonrpc": "2.0", "id": msg_id, "error": {"code": -32601, "message": f"Method not found: {method}"}}
+    return {"jsonrpc": "2.0", "id": msg_id, "result": result}
+
+
+def main() -> None:
+    ensure_directories()
+    from src.utils.logger import configure_logging
+    configure_logging()
+    # 标准输出只用于协议消息，控制台日志改写到标准错误
+    for handler in logging.getLogger().handlers:
+        if type(handler) is logging.StreamHandler:
+            handler.setStream(sys.stderr)
+
+    for line in sys.stdin:
+        line = line.strip()
+        if not line:
+            continue
+        try:
+            message = json.loads(line)
+        except json.JSONDecodeError as e:
+            response = {"jsonrpc": "2.0", "id": None, "error": {"code": -32700, "message": f"Parse error: {e}"}}
+        else:
+            response = handle(message)
+        if response is not None:
+            sys.stdout.write(json.dumps(response, ensure_ascii=False) + "\n")
+            sys.stdout.flush()
+
+
+if __name__ == "__main__":
+    main()
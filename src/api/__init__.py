@@ -13,9 +13,13 @@ from .project_api import (
 from .workflow_api import (
     api_start_workflow,
     api_get_workflow_status,
+    api_get_session_progress,
+    api_list_running_jobs,
     api_pause_workflow,
     api_resume_workflow,
-    api_stop_workflow
+    api_stop_workflow,
+    api_get_session_trace,
+    api_export_session_trace
 )
 
 from .agent_api import (
@@ -31,11 +35,33 @@ from .questioning_api import (
     api_get_clarification_status,
     api_finish_clarification,
     api_submit_summary,
+    api_list_sessions,
+    api_search_sessions,
+    api_set_session_tags,
+    api_set_session_folder,
+    api_list_tags,
+    api_delete_session,
+    api_rename_session,
+    api_duplicate_session,
+    api_set_confidential_terms,
+    api_get_confidential_terms,
+    api_recover_session,
+    recover_interrupted_sessions,
+    api_list_domain_templates,
+    api_list_prompts,
+    api_reload_prompts,
+    api_get_summary,
+    api_regenerate_summary,
+    api_update_summary,
+    api_rollback_summary,
+    api_get_analytics,
 )
 
 from .model_api import (
     api_call_ai_model,
     api_test_model_connection,
+    api_test_ai_connection,
+    api_validate_api_key,
     api_list_available_models,
     api_get_model_config
 )
@@ -44,6 +70,24 @@ from .clarification_api import (
     run_clarification_ai,
 )
 
+from .export_api import (
+    api_export_result,
+    api_export_session_bundle,
+    api_import_session_bundle
+)
+
+from .system_api import (
+    api_get_storage_paths,
+    api_open_data_directory,
+    api_save_config,
+    api_get_config,
+    api_get_cache_stats,
+    api_get_rate_limit_stats,
+    api_get_system_health,
+    api_get_audit_log,
+    api_clear_cache
+)
+
 __all__ = [
     # Project API
     "api_create_project",
@@ -55,9 +99,13 @@ __all__ = [
     # Workflow API
     "api_start_workflow",
     "api_get_workflow_status",
+    "api_get_session_progress",
+    "api_list_running_jobs",
     "api_pause_workflow",
     "api_resume_workflow",
     "api_stop_workflow",
+    "api_get_session_trace",
+    "api_export_session_trace",
     
     # Questioning API
     "api_start_clarification_session",
@@ -65,6 +113,26 @@ __all__ = [
     "api_get_clarification_status",
     "api_finish_clarification",
     "api_submit_summary",
+    "api_list_sessions",
+    "api_search_sessions",
+    "api_set_session_tags",
+    "api_set_session_folder",
+    "api_list_tags",
+    "api_delete_session",
+    "api_rename_session",
+    "api_duplicate_session",
+    "api_set_confidential_terms",
+    "api_get_confidential_terms",
+    "api_recover_session",
+    "recover_interrupted_sessions",
+    "api_list_domain_templates",
+    "api_list_prompts",
+    "api_reload_prompts",
+    "api_get_summary",
+    "api_regenerate_summary",
+    "api_update_summary",
+    "api_rollback_summary",
+    "api_get_analytics",
     # Agent API
     "api_list_agents",
     "api_create_agent",
@@ -74,9 +142,27 @@ __all__ = [
     # Model API
     "api_call_ai_model",
     "api_test_model_connection",
+    "api_test_ai_connection",
+    "api_validate_api_key",
     "api_list_available_models",
     "api_get_model_config"
     ,
     # Clarification API
-    "run_clarification_ai"
+    "run_clarification_ai",
+
+    # Export API
+    "api_export_result",
+    "api_export_session_bundle",
+    "api_import_session_bundle",
+
+    # System API
+    "api_get_storage_paths",
+    "api_open_data_directory",
+    "api_save_config",
+    "api_get_config",
+    "api_get_cache_stats",
+    "api_get_rate_limit_stats",
+    "api_get_system_health",
+    "api_get_audit_log",
+    "api_clear_cache"
 ]
@@ -6,6 +6,7 @@ from typing import Dict, List, Any
 from datetime import datetime
 
 from ..utils.logger import get_logger
+from ..utils.errors import ErrorCode, app_error, error_response
 
 logger = get_logger(__name__)
 
@@ -55,10 +56,7 @@ def api_list_agents() -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"列出智能体失败: {e}")
-        return {
-            "success": False,
-            "error": f"列出智能体时发生错误: {str(e)}"
-        }
+        return error_response(e, f"列出智能体时发生错误: {str(e)}")
 
 
 def api_create_agent(name: str, role: str, model: str = "gpt-4", description: str = "") -> Dict[str, Any]:
@@ -87,20 +85,14 @@ def api_create_agent(name: str, role: str, model: str = "gpt-4", description: st
         
     except Exception as e:
         logger.error(f"创建智能体失败: {e}")
-        return {
-            "success": False,
-            "error": f"创建智能体时发生错误: {str(e)}"
-        }
+        return error_response(e, f"创建智能体时发生错误: {str(e)}")
 
 
 def api_get_agent_status(agent_id: str) -> Dict[str, Any]:
     """获取智能体状态"""
     try:
         if agent_id not in agents:
-            return {
-                "success": False,
-                "error": "智能体不存在"
-            }
+            return app_error(ErrorCode.NOT_FOUND, "智能体不存在")
         
         agent = agents[agent_id]
         
@@ -111,20 +103,14 @@ def api_get_agent_status(agent_id: str) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"获取智能体状态失败: {e}")
-        return {
-            "success": False,
-            "error": f"获取智能体状态时发生错误: {str(e)}"
-        }
+        return error_response(e, f"获取智能体状态时发生错误: {str(e)}")
 
 
 def api_configure_agent(agent_id: str, config: Dict[str, Any]) -> Dict[str, Any]:
     """配置智能体"""
     try:
         if agent_id not in agents:
-            return {
-                "success": False,
-                "error": "智能体不存在"
-            }
+            return app_error(ErrorCode.NOT_FOUND, "智能体不存在")
         
         agent = agents[agent_id]
         
@@ -147,7 +133,4 @@ def api_configure_agent(agent_id: str, config: Dict[str, Any]) -> Dict[str, Any]
         
     except Exception as e:
         logger.error(f"配置智能体失败: {e}")
-        return {
-            "success": False,
-            "error": f"配置智能体时发生错误: {str(e)}"
-        }
+        return error_response(e, f"配置智能体时发生错误: {str(e)}")
@@ -0,0 +1,211 @@
+"""
+Export API
+"""
+
+import json
+import zipfile
+from datetime import datetime
+from pathlib import Path
+from typing import Dict, Any, Optional
+
+from ..utils.logger import get_logger
+from ..utils.storage import SessionStorage, is_valid_storage_id, new_session_id, storage_file
+from ..utils.report import EXPORT_FORMATS, build_report, render_report, write_pdf
+from ..utils.safety import confidential_terms, redact_data, set_confidential_terms
+from ..utils.errors import ErrorCode, app_error, error_response
+from .workflow_api import workflow_sessions
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+
+def api_export_result(session_id: str, format: str = "markdown", output_path: Optional[str] = None) -> Dict[str, Any]:
+    """导出会话结果（Markdown / JSON / HTML / PDF），返回文件位置"""
+    try:
+        fmt = (format or "markdown").lower()
+        if fmt == "md":
+            fmt = "markdown"
+        if fmt not in EXPORT_FORMATS:
+            return app_error(ErrorCode.UNSUPPORTED, f"不支持的导出格式: {format}", session_id)
+        
+        session = SessionStorage().load_session(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "会话不存在", session_id)
+        
+        report = build_report(session)
+        
+        if output_path:
+            export_file = Path(output_path)
+        else:
+            export_file = app_config.data_dir / "exports" / f"{session_id}.{EXPORT_FORMATS[fmt]}"
+        export_file.parent.mkdir(parents=True, exist_ok=True)
+        
+        if fmt == "pdf":
+            write_pdf(report, export_file)
+        else:
+            with open(export_file, 'w', encoding='utf-8') as f:
+                f.write(render_report(report, fmt))
+        
+        logger.info(f"导出结果成功: {export_file}")
+        
+        return {
+            "success": True,
+            "data": {
+                "path": str(export_file.resolve()),
+                "format": fmt
+            }
+        }
+        
+    except Exception as e:
+        logger.error(f"导出结果失败: {e}")
+        return error_response(e, f"导出结果时发生错误: {str(e)}", session_id=session_id)
+
+
+# 会话包格式版本；导入时拒绝更高版本
+BUNDLE_FORMAT = "ideaarchitect.session-bundle"
+BUNDLE_VERSION = 1
+
+
+def api_export_session_bundle(
+    session_id: str,
+    output_path: Optional[str] = None,
+    include_sensitive: bool = False,
+) -> Dict[str, Any]:
+    """导出会话包（zip）：想法、澄清问答、总结历史、关联项目与工作流结果，用于在另一台机器上导入
+
+    默认与普通导出一样遮盖包内所有文件中的密钥、邮箱与保密词（不含保密词列表本身）；
+    include_sensitive 为真时导出原始内容，仅用于在自己的机器之间完整迁移。
+    """
+    try:
+        session = SessionStorage().load_session(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "会话不存在", session_id)
+        
+        manifest = {
+            "format": BUNDLE_FORMAT,
+            "version": BUNDLE_VERSION,
+            "app_version": app_config.app_version,
+            "session_id": session_id,
+            "exported_at": datetime.now().isoformat(),
+            "redacted": not include_sensitive,
+            "files": ["session.json", "report.md"]
+        }
+        
+        project = None
+        if is_valid_storage_id(session.get("project_id")):
+            project_file = storage_file(app_config.projects_dir, session["project_id"])
+            if project_file.exists():
+                project = json.loads(project_file.read_text(encoding="utf-8"))
+                manifest["files"].append("project.json")
+        
+        workflow = workflow_sessions.get(session.get("workflow_session_id") or "")
+        if workflow:
+            manifest["files"].append("workflow.json")
+        
+        report = render_report(build_report(session), "markdown")
+        if not include_sensitive:
+            terms = confidential_terms(session_id)
+            session = redact_data({key: value for key, value in session.items() if key != "confidential_terms"}, terms)
+            project = redact_data(project, terms)
+            workflow = redact_data(json.loads(json.dumps(workflow, default=str)), terms) if workflow else workflow
+        
+        if output_path:
+            bundle_file = Path(output_path)
+        else:
+            bundle_file = app_config.data_dir / "exports" / f"{session_id}.bundle.zip"
+        bundle_file.parent.mkdir(parents=True, exist_ok=True)
+        
+        with zipfile.ZipFile(bundle_file, "w", zipfile.ZIP_DEFLATED) as bundle:
+            bundle.writestr("manifest.json", json.dumps(manifest, ensure_ascii=False, indent=2))
+            bundle.writestr("session.json", json.dumps(session, ensure_ascii=False, indent=2))
+            # 便于直接阅读的报告（与普通导出一样做敏感信息遮盖）
+            bundle.writestr("report.md", report)
+            if project is not None:
+                bundle.writestr("project.json", json.dumps(project, ensure_ascii=False, indent=2))
+            if workflow:
+                bundle.writestr("workflow.json", json.dumps(workflow, ensure_ascii=False, indent=2, default=str))
+        
+        logger.info(f"会话包已导出: {bundle_file}")
+        
+        return {
+            "success": True,
+            "data": {
+                "path": str(bundle_file.resolve()),
+                "files": manifest["files"],
+                "redacted": manifest["redacted"]
+            }
+        }
+        
+    except Exception as e:
+        logger.error(f"导出会话包失败: {e}")
+        return error_response(e, f"导出会话包时发生错误: {str(e)}", session_id=session_id)
+
+
+def api_import_session_bundle(path: str) -> Dict[str, Any]:
+    """导入会话包；本地已存在同 ID 的会话时使用新 ID，已存在的项目不会被覆盖"""
+    try:
+        bundle_file = Path(path)
+        if not bundle_file.is_file():
+            return app_error(ErrorCode.NOT_FOUND, f"文件不存在: {path}")
+        
+        with zipfile.ZipFile(bundle_file) as bundle:
+            names = set(bundle.namelist())
+            if "manifest.json" not in names or "session.json" not in names:
+                return app_error(ErrorCode.INVALID_INPUT, "不是有效的会话包")
+            manifest = json.loads(bundle.read("manifest.json"))
+            if manifest.get("format") != BUNDLE_FORMAT or int(manifest.get("version", 0)) > BUNDLE_VERSION:
+                return app_error(ErrorCode.UNSUPPORTED, f"不支持的会话包版本: {manifest.get('format')} v{manifest.get('version')}")
+            session = json.loads(bundle.read("session.json"))
+            project = json.loads(bundle.read("project.json")) if "project.json" in names else None
+            workflow = json.loads(bundle.read("workflow.json")) if "workflow.json" in names else None
+        
+        # ID 会被用作文件名或内存中的键，必须先校验，避免构造的会话包写到数据目录之外
+        for name, item in (("session.json", session), ("project.json", project), ("workflow.json", workflow)):
+            if item is None and name != "session.json":
+                continue
+            if not isinstance(item, dict) or not is_valid_storage_id(item.get("id")):
+                return app_error(ErrorCode.INVALID_INPUT, f"会话包中的 {name} 缺少有效的 id")
+        # 关联的项目与工作流 ID 之后会被用来拼接项目文件路径（如再次导出时），同样需要校验
+        for field in ("project_id", "workflow_session_id"):
+            if session.get(field) is not None and not is_valid_storage_id(session[field]):
+                return app_error(ErrorCode.INVALID_INPUT, f"会话包中的 session.json 含有无效的 {field}")
+        
+        storage = SessionStorage()
+        original_id = session["id"]
+        if storage.load_session(original_id):
+            session["id"] = new_session_id()
+        session["imported_from"] = original_id
+        session["imported_at"] = datetime.now().isoformat()
+        
+        if project:
+            project_file = storage_file(app_config.projects_dir, project["id"])
+            if not project_file.exists():
+                project_file.parent.mkdir(parents=True, exist_ok=True)
+                project_file.write_text(json.dumps(project, ensure_ascii=False, indent=2), encoding="utf-8")
+        
+        if workflow and workflow["id"] not in workflow_sessions:
+            # 工作流只保存在内存中；导入后作为已结束的工作流供查看结果
+            if workflow.get("status") in ("queued", "running", "paused"):
+                workflow["status"] = "stopped"
+            workflow_sessions[workflow["id"]] = workflow
+        
+        if not storage.save_session(session["id"], session):
+            return app_error(ErrorCode.STORAGE, "保存导入的会话失败")
+        set_confidential_terms(session["id"], session.get("confidential_terms") or [])
+        
+        logger.info(f"会话包已导入: {bundle_file} -> {session['id']}")
+        
+        return {
+            "success": True,
+            "session_id": session["id"],
+            "data": {
+                "session_id": session["id"],
+                "imported_from": original_id,
+                "project_id": session.get("project_id"),
+                "workflow_session_id": session.get("workflow_session_id")
+            }
+        }
+        
+    except Exception as e:
+        logger.error(f"导入会话包失败: {e}")
+        return error_response(e, f"导入会话包时发生错误: {str(e)}")
@@ -3,57 +3,87 @@ AI Model API
 """
 
 import asyncio
-from typing import Dict, List, Any
+from typing import Dict, List, Any, Optional
 from datetime import datetime
 
 from ..utils.logger import get_logger
-from config.app_config import ModelConfig
+from ..utils.errors import ErrorCode, app_error, error_response
+from ..utils.providers import (
+    PROVIDER_SETTINGS,
+    configured_providers,
+    list_provider_models,
+    model_metadata,
+    ping_provider,
+    validate_api_key,
+)
+from config.app_config import app_config, ModelConfig
 
 logger = get_logger(__name__)
 
 
-def api_list_available_models() -> Dict[str, Any]:
-    """列出可用的AI模型"""
+def _static_models(provider: str) -> List[Dict[str, Any]]:
+    config = ModelConfig.PROVIDERS.get(provider)
+    if not config:
+        return []
+    return [
+        {"provider": provider, "model": model, **model_metadata(model), "source": "static"}
+        for model in config["models"]
+    ]
+
+
+def api_list_available_models(provider: Optional[str] = None, refresh: bool = False) -> Dict[str, Any]:
+    """列出可用的AI模型
+
+    已配置密钥的提供商（以及本地 Ollama）会查询其模型列表接口并缓存结果，
+    查询失败或未配置时回退到内置的模型清单。
+    """
     try:
-        models = []
+        if provider and provider not in PROVIDER_SETTINGS and provider not in ModelConfig.PROVIDERS:
+            return app_error(ErrorCode.UNSUPPORTED, f"不支持的提供商: {provider}")
         
-        for provider, config in ModelConfig.PROVIDERS.items():
-            for model in config["models"]:
-                models.append({
-                    "provider": provider,
-                    "model": model,
-                    "api_base": config["api_base"],
-                    "supports_streaming": config["supports_streaming"]
-                })
+        providers = [provider] if provider else list(dict.fromkeys([*PROVIDER_SETTINGS, *ModelConfig.PROVIDERS]))
+        live = set(configured_providers())
+        models = []
+        errors = {}
+        
+        for name in providers:
+            fetched = None
+            if not app_config.offline and (name in live or name == "ollama"):
+                try:
+                    fetched = list_provider_models(name, refresh=refresh)
+                except Exception as e:
+                    errors[name] = str(e)
+                    logger.warning(f"查询模型列表失败: {name} - {e}")
+            if fetched:
+                models.extend({**m, "source": "remote"} for m in fetched)
+            else:
+                models.extend(_static_models(name))
+        
+        for model in models:
+            config = ModelConfig.PROVIDERS.get(model["provider"], {})
+            model.setdefault("api_base", config.get("api_base"))
+            model.setdefault("supports_streaming", config.get("supports_streaming", True))
         
         return {
             "success": True,
-            "data": models
+            "data": models,
+            "errors": errors
         }
         
     except Exception as e:
         logger.error(f"列出可用模型失败: {e}")
-        return {
-            "success": False,
-            "error": f"列出可用模型时发生错误: {str(e)}"
-        }
+        return error_response(e, f"列出可用模型时发生错误: {str(e)}")
 
 
 def api_get_model_config(provider: str, model: str) -> Dict[str, Any]:
     """获取模型配置"""
     try:
         if provider not in ModelConfig.PROVIDERS:
-            return {
-                "success": False,
-                "error": f"不支持的提供商: {provider}"
-            }
+            return app_error(ErrorCode.UNSUPPORTED, f"不支持的提供商: {provider}")
         
         provider_config = ModelConfig.PROVIDERS[provider]
         if model not in provider_config["models"]:
-            return {
-                "success": False,
-                "error": f"提供商 {provider} 不支持模型 {model}"
-            }
+            return app_error(ErrorCode.UNSUPPORTED, f"提供商 {provider} 不支持模型 {model}")
         
         config = {
             "provider": provider,
@@ -69,10 +99,7 @@ def api_get_model_config(provider: str, model: str) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"获取模型配置失败: {e}")
-        return {
-            "success": False,
-            "error": f"获取模型配置时发生错误: {str(e)}"
-        }
+        return error_response(e, f"获取模型配置时发生错误: {str(e)}")
 
 
 async def api_call_ai_model(provider: str, model: str, messages: List[Dict[str, str]], config: Dict[str, Any] = None) -> Dict[str, Any]:
@@ -80,17 +107,11 @@ async def api_call_ai_model(provider: str, model: str, messages: List[Dict[str,
     try:
         # 验证提供商和模型
         if provider not in ModelConfig.PROVIDERS:
-            return {
-                "success": False,
-                "error": f"不支持的提供商: {provider}"
-            }
+            return app_error(ErrorCode.UNSUPPORTED, f"不支持的提供商: {provider}")
         
         provider_config = ModelConfig.PROVIDERS[provider]
         if model not in provider_config["models"]:
-            return {
-                "success": False,
-                "error": f"提供商 {provider} 不支持模型 {model}"
-            }
+            return app_error(ErrorCode.UNSUPPORTED, f"提供商 {provider} 不支持模型 {model}")
         
         # 模拟AI模型调用
         await asyncio.sleep(1)  # 模拟网络延迟
@@ -122,41 +143,85 @@ async def api_call_ai_model(provider: str, model: str, messages: List[Dict[str,
         
     except Exception as e:
         logger.error(f"AI模型调用失败: {e}")
-        return {
-            "success": False,
-            "error": f"AI模型调用时发生错误: {str(e)}"
-        }
+        return error_response(e, f"AI模型调用时发生错误: {str(e)}")
 
 
 async def api_test_model_connection(provider: str, model: str) -> Dict[str, Any]:
-    """测试模型连接"""
+    """测试模型连接（向提供商发送一次最小化真实请求）"""
     try:
-        # 模拟连接测试
-        await asyncio.sleep(0.5)
+        if provider not in PROVIDER_SETTINGS:
+            return app_error(ErrorCode.UNSUPPORTED, f"不支持的提供商: {provider}")
         
-        # 简单的连接测试
-        test_messages = [{"role": "user", "content": "Hello"}]
-        result = await api_call_ai_model(provider, model, test_messages)
+        result = await asyncio.to_thread(ping_provider, provider, model)
         
         if result["success"]:
             return {
                 "success": True,
                 "data": {
                     "provider": provider,
-                    "model": model,
+                    "model": result["model"],
                     "status": "connected",
-                    "response_time": 0.5
+                    "response_time": result["latency_ms"] / 1000
                 }
             }
         else:
+            return app_error(ErrorCode.PROVIDER, f"连接测试失败: {result['error']}", provider=provider)
+        
+    except Exception as e:
+        logger.error(f"模型连接测试失败: {e}")
+        return error_response(e, f"模型连接测试时发生错误: {str(e)}")
+
+
+async def api_test_ai_connection() -> Dict[str, Any]:
+    """测试所有已配置提供商的连通性，逐个返回成功与否、延迟和错误信息"""
+    try:
+        if app_config.offline:
             return {
-                "success": False,
-                "error": f"连接测试失败: {result['error']}"
+                "success": True,
+                "offline": True,
+                "data": [],
+                "message": "离线模式：不连接任何模型提供商，使用内置规则生成问题与总结"
             }
         
+        providers = configured_providers()
+        if not providers:
+            return app_error(ErrorCode.NOT_CONFIGURED, "未配置任何AI模型API密钥")
+        
+        results = await asyncio.gather(*[
+            asyncio.to_thread(ping_provider, provider) for provider in providers
+        ])
+        
+        for result in results:
+            if result["success"]:
+                logger.info(f"提供商连接正常: {result['provider']} ({result['latency_ms']}ms)")
+            else:
+                logger.warning(f"提供商连接失败: {result['provider']} - {result['error']}")
+        
+        return {
+            "success": any(r["success"] for r in results),
+            "offline": False,
+            "data": results
+        }
+        
     except Exception as e:
-        logger.error(f"模型连接测试失败: {e}")
+        logger.error(f"AI连接测试失败: {e}")
+        return error_response(e, f"AI连接测试时发生错误: {str(e)}")
+
+
+async def api_validate_api_key(provider: str, key: str) -> Dict[str, Any]:
+    """在保存前校验API密钥（格式检查 + 提供商模型列表接口验证）"""
+    try:
+        if provider not in PROVIDER_SETTINGS:
+            return app_error(ErrorCode.UNSUPPORTED, f"不支持的提供商: {provider}")
+        
+        result = await asyncio.to_thread(validate_api_key, provider, key)
+        logger.info(f"API密钥校验: {provider} -> {result['status']}")
+        
         return {
-            "success": False,
-            "error": f"模型连接测试时发生错误: {str(e)}"
+            "success": True,
+            "data": result
         }
+        
+    except Exception as e:
+        logger.error(f"API密钥校验失败: {e}")
+        return error_response(e, f"API密钥校验时发生错误: {str(e)}")
@@ -11,6 +11,7 @@ from datetime import datetime
 from ..models.project import Project, ProjectStatus, ProjectInput
 from ..utils.storage import ProjectStorage
 from ..utils.logger import get_logger
+from ..utils.errors import ErrorCode, app_error, error_response
 from config.app_config import app_config
 
 logger = get_logger(__name__)
@@ -35,10 +36,7 @@ def api_create_project(project_data) -> Dict[str, Any]:
 
         # 验证输入
         if not name or not name.strip():
-            return {
-                "success": False,
-                "error": "项目名称不能为空"
-            }
+            return app_error(ErrorCode.INVALID_INPUT, "项目名称不能为空")
         
         # 创建项目输入
         project_input = ProjectInput(
@@ -60,10 +58,7 @@ def api_create_project(project_data) -> Dict[str, Any]:
         # 保存项目
         success = storage.save_project(project)
         if not success:
-            return {
-                "success": False,
-                "error": "保存项目失败"
-            }
+            return app_error(ErrorCode.STORAGE, "保存项目失败")
         
         logger.info(f"创建项目成功: {project.name} (ID: {project.id})")
         
@@ -75,10 +70,7 @@ def api_create_project(project_data) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"创建项目失败: {e}")
-        return {
-            "success": False,
-            "error": f"创建项目时发生错误: {str(e)}"
-        }
+        return error_response(e, f"创建项目时发生错误: {str(e)}")
 
 
 def api_load_project(project_id: str) -> Dict[str, Any]:
@@ -86,10 +78,7 @@ def api_load_project(project_id: str) -> Dict[str, Any]:
     try:
         project = storage.load_project(project_id)
         if not project:
-            return {
-                "success": False,
-                "error": "项目不存在"
-            }
+            return app_error(ErrorCode.NOT_FOUND, "项目不存在")
         
         logger.info(f"加载项目成功: {project.name} (ID: {project_id})")
         
@@ -100,10 +89,7 @@ def api_load_project(project_id: str) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"加载项目失败: {e}")
-        return {
-            "success": False,
-            "error": f"加载项目时发生错误: {str(e)}"
-        }
+        return error_response(e, f"加载项目时发生错误: {str(e)}")
 
 
 def api_save_project(project_data: Dict[str, Any]) -> Dict[str, Any]:
@@ -116,10 +102,7 @@ def api_save_project(project_data: Dict[str, Any]) -> Dict[str, Any]:
         # 保存项目
         success = storage.save_project(project)
         if not success:
-            return {
-                "success": False,
-                "error": "保存项目失败"
-            }
+            return app_error(ErrorCode.STORAGE, "保存项目失败")
         
         logger.info(f"保存项目成功: {project.name} (ID: {project.id})")
         
@@ -130,10 +113,7 @@ def api_save_project(project_data: Dict[str, Any]) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"保存项目失败: {e}")
-        return {
-            "success": False,
-            "error": f"保存项目时发生错误: {str(e)}"
-        }
+        return error_response(e, f"保存项目时发生错误: {str(e)}")
 
 
 def api_list_projects() -> Dict[str, Any]:
@@ -158,10 +138,7 @@ def api_list_projects() -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"列出项目失败: {e}")
-        return {
-            "success": False,
-            "error": f"列出项目时发生错误: {str(e)}"
-        }
+        return error_response(e, f"列出项目时发生错误: {str(e)}")
 
 
 def api_delete_project(project_id: str) -> Dict[str, Any]:
@@ -170,18 +147,12 @@ def api_delete_project(project_id: str) -> Dict[str, Any]:
         # 检查项目是否存在
         project = storage.load_project(project_id)
         if not project:
-            return {
-                "success": False,
-                "error": "项目不存在"
-            }
+            return app_error(ErrorCode.NOT_FOUND, "项目不存在")
         
         # 删除项目
         success = storage.delete_project(project_id)
         if not success:
-            return {
-                "success": False,
-                "error": "删除项目失败"
-            }
+            return app_error(ErrorCode.STORAGE, "删除项目失败")
         
         logger.info(f"删除项目成功: {project.name} (ID: {project_id})")
         
@@ -192,7 +163,4 @@ def api_delete_project(project_id: str) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"删除项目失败: {e}")
-        return {
-            "success": False,
-            "error": f"删除项目时发生错误: {str(e)}"
-        }
+        return error_response(e, f"删除项目时发生错误: {str(e)}")
@@ -6,14 +6,35 @@ from __future__ import annotations
 import json
 import os
 import re
+import time
 from dataclasses import dataclass, asdict
 from datetime import datetime
-from typing import Any, Dict, List, Optional, Tuple
-
-import requests
-
-from ..utils.logger import get_logger
-from ..utils.storage import SessionStorage
+from typing import Any, Callable, Dict, List, Optional
+
+from ..utils.logger import get_logger, get_log_context, log_context
+from ..utils.event_bus import event_bus, EventTypes
+from ..utils.storage import SessionStorage, new_session_id
+from ..utils.providers import (
+    PROVIDER_SETTINGS,
+    ModelError,
+    ModelErrorKind,
+    chat_completion,
+    chat_completion_stream,
+    get_provider_settings,
+    mock_provider_selected,
+    MOCK_PROVIDER,
+    normalize_api_base as _normalize_api_base,
+    bootstrap_env_from_toml as _bootstrap_provider_env_from_toml,
+)
+from ..utils.prompts import render_prompt, load_prompt_overrides, list_prompts
+from ..utils.intake import IntakeError, resolve_idea_seed
+from ..utils.errors import AppError, ErrorCode, app_error, classify_exception, error_response, forward_error
+from ..utils.audit import record_audit
+from ..utils.search import search_sessions
+from ..utils.safety import check_idea, sanitize_prompt_text, set_confidential_terms
+from ..utils.i18n import language_instruction, output_language, t
+from ..utils.templates import clarifier_guidance, get_domain_template, list_domain_templates, summarizer_guidance
+from config.app_config import app_config
 
 logger = get_logger(__name__)
 
@@ -31,12 +52,13 @@ class ClarificationSessionManager:
     def __init__(self):
         self.storage = SessionStorage()
 
-    def create_session(self, idea_seed: Dict[str, Any]) -> str:
-        session_id = f"clar_{datetime.now().strftime('%Y%m%d_%H%M%S_%f')}"
+    def create_session(self, idea_seed: Dict[str, Any], model_overrides: Optional[Dict[str, Any]] = None) -> str:
+        session_id = new_session_id()
         data = {
             "id": session_id,
             "status": "running",
             "idea_seed": idea_seed,
+            "model_overrides": model_overrides,
             "project_id": None,
             "questions": [],  # list of ClarificationQuestion as dict
             "created_at": datetime.now().isoformat(),
@@ -64,6 +86,17 @@ class ClarificationSessionManager:
         pending.sort(key=lambda q: q.priority, reverse=True)
         return pending[0]
 
+    def duplicate(self, session: Dict[str, Any]) -> Dict[str, Any]:
+        """复制会话（保留问题与回答，不关联原项目与工作流）"""
+        copy = json.loads(json.dumps(session))
+        copy["id"] = new_session_id()
+        copy["title"] = f"{_session_title(session)}（副本）"
+        copy["project_id"] = None
+        copy.pop("workflow_session_id", None)
+        copy["created_at"] = datetime.now().isoformat()
+        self.save(copy)
+        return copy
+
     def submit_answer(self, session: Dict[str, Any], slot_name: str, answer: str) -> None:
         for q in session.get("questions", []):
             if q["slot_name"] == slot_name and not q.get("answer"):
@@ -72,52 +105,116 @@ class ClarificationSessionManager:
         self.save(session)
 
 
-def _format_summary_text(summary: Dict[str, Any]) -> str:
-    """Format a structured summary dict into a readable handoff section."""
+def _session_title(session: Dict[str, Any]) -> str:
+    """会话标题：用户重命名的标题优先，否则取想法原文开头"""
+    if session.get("title"):
+        return session["title"]
+    raw = ((session.get("idea_seed") or {}).get("raw_text") or "").strip()
+    return raw[:30] or session.get("id", "")
+
+
+def _session_overview(session: Dict[str, Any]) -> Dict[str, Any]:
+    questions = session.get("questions", [])
+    return {
+        "id": session.get("id"),
+        "title": _session_title(session),
+        "status": session.get("status"),
+        "tags": session.get("tags") or [],
+        "folder": session.get("folder"),
+        "project_id": session.get("project_id"),
+        "workflow_session_id": session.get("workflow_session_id"),
+        "workflow_stale": bool(session.get("workflow_stale")),
+        "question_count": len(questions),
+        "answered_count": len([1 for q in questions if q.get("answer")]),
+        "has_summary": bool(session.get("summary")),
+        "created_at": session.get("created_at"),
+        "updated_at": session.get("updated_at"),
+    }
+
+
+def _store_summary(session: Dict[str, Any], summary: Dict[str, Any], source: str) -> int:
+    """Set the current summary and append it to summary_history; returns the new version number."""
+    history = session.setdefault('summary_history', [])
+    version = (history[-1]['version'] if history else 0) + 1
+    history.append({
+        "version": version,
+        "source": source,
+        "summary": summary,
+        "created_at": datetime.now().isoformat(),
+    })
+    session['summary'] = summary
+    session['summary_version'] = version
+    return version
+
+
+# 总结中的文本字段与列表字段（其余字段如 domain_fields 另行校验）
+SUMMARY_TEXT_FIELDS = ("title", "refined_idea")
+SUMMARY_LIST_FIELDS = (
+    "user_segments", "core_pain_points", "key_features", "constraints",
+    "success_metrics", "risks", "next_steps", "qa_pairs",
+)
+
+
+def _validate_summary_edit(changes: Any) -> Dict[str, Any]:
+    """Validate a partial summary edit; raises ValueError naming the offending field."""
+    if not isinstance(changes, dict) or not changes:
+        raise ValueError("summary changes must be a non-empty object")
+    cleaned: Dict[str, Any] = {}
+    for key, value in changes.items():
+        if key in SUMMARY_TEXT_FIELDS:
+            if not isinstance(value, str):
+                raise ValueError(f"{key} must be a string")
+            cleaned[key] = value.strip()
+        elif key in SUMMARY_LIST_FIELDS:
+            if not isinstance(value, list) or not all(isinstance(item, str) for item in value):
+                raise ValueError(f"{key} must be a list of strings")
+            cleaned[key] = [item.strip() for item in value if item.strip()]
+        elif key == "domain_fields":
+            if not isinstance(value, dict) or not all(isinstance(item, str) for item in value.values()):
+                raise ValueError("domain_fields must be an object with string values")
+            cleaned[key] = {name: item.strip() for name, item in value.items()}
+        else:
+            raise ValueError(f"unknown summary field: {key}")
+    return cleaned
+
+
+def _record_handoff(session: Dict[str, Any], workflow_session_id: Optional[str]) -> None:
+    """Remember which workflow was started from which summary version."""
+    if workflow_session_id:
+        session['workflow_session_id'] = workflow_session_id
+    session['handoff_summary_version'] = session.get('summary_version')
+    session['workflow_stale'] = False
+
+
+def _invalidate_handoff(session: Dict[str, Any]) -> List[str]:
+    """Mark the workflow started from an older summary version as stale; returns its id if any."""
+    if session.get('workflow_session_id') and session.get('handoff_summary_version') != session.get('summary_version'):
+        session['workflow_stale'] = True
+        return [session['workflow_session_id']]
+    return []
+
+
+def _format_summary_text(summary: Dict[str, Any], language: Optional[str] = None) -> str:
+    """Format a structured summary dict into a readable handoff section, labelled in the output language."""
     if not isinstance(summary, dict):
         return str(summary)
     lines: List[str] = []
+
+    def add(label: str, value: Any) -> None:
+        if isinstance(value, list):
+            value = t("handoff.separator", language).join(str(x) for x in value)
+        lines.append(t("report.field", language, label=label, value=value))
+
     title = summary.get('title') or summary.get('idea_title') or ''
     if title:
-        lines.append(f"标题：{title}")
+        add(t("handoff.title", language), title)
     refined = summary.get('refined_idea') or summary.get('summary') or summary.get('refined') or ''
     if refined:
-        lines.append(f"精炼概述：{refined}")
-    if summary.get('user_segments'):
-        segs = summary.get('user_segments')
-        if isinstance(segs, list):
-            segs = '；'.join(str(x) for x in segs)
-        lines.append(f"目标用户：{segs}")
-    if summary.get('core_pain_points'):
-        pains = summary.get('core_pain_points')
-        if isinstance(pains, list):
-            pains = '；'.join(str(x) for x in pains)
-        lines.append(f"核心痛点：{pains}")
-    if summary.get('key_features'):
-        feats = summary.get('key_features')
-        if isinstance(feats, list):
-            feats = '；'.join(str(x) for x in feats)
-        lines.append(f"关键特性：{feats}")
-    if summary.get('constraints'):
-        cons = summary.get('constraints')
-        if isinstance(cons, list):
-            cons = '；'.join(str(x) for x in cons)
-        lines.append(f"约束条件：{cons}")
-    if summary.get('success_metrics'):
-        mets = summary.get('success_metrics')
-        if isinstance(mets, list):
-            mets = '；'.join(str(x) for x in mets)
-        lines.append(f"成功指标：{mets}")
-    if summary.get('risks'):
-        risks = summary.get('risks')
-        if isinstance(risks, list):
-            risks = '；'.join(str(x) for x in risks)
-        lines.append(f"风险：{risks}")
-    if summary.get('next_steps'):
-        steps = summary.get('next_steps')
-        if isinstance(steps, list):
-            steps = '；'.join(str(x) for x in steps)
-        lines.append(f"下一步：{steps}")
+        add(t("section.refined_idea", language), refined)
+    for field in ('user_segments', 'core_pain_points', 'key_features', 'constraints',
+                  'success_metrics', 'risks', 'next_steps'):
+        if summary.get(field):
+            add(t(f"section.{field}", language), summary[field])
     return "\n".join(lines)
 
 
@@ -130,8 +227,17 @@ def _heuristic_summary(session: Dict[str, Any]) -> Dict[str, Any]:
         if q.get('answer'):
             answered.append(q)
             qa.append(f"{q['question']} -> {q['answer']}")
+    language = seed.get('language')
+    template = get_domain_template(seed.get('domain'), language)
+    domain_fields = {
+        key: q['answer']
+        for key, _ in (template["fields"] if template else [])
+        for q in answered
+        if q['slot_name'] == key
+    }
+    domain_name = template["label"] if template else seed.get('domain') or t("fallback.default_domain", language)
     return {
-        "title": (seed.get('domain') or '概念') + "总结",
+        "title": t("fallback.summary_title", language, domain=domain_name),
         "refined_idea": base,
         "key_features": [],
         "user_segments": [],
@@ -139,143 +245,229 @@ def _heuristic_summary(session: Dict[str, Any]) -> Dict[str, Any]:
         "constraints": [],
         "success_metrics": [],
         "risks": [],
-        "next_steps": ["进入多智能体协作讨论，细化方案与里程碑"],
+        "next_steps": [t("fallback.next_step", language)],
+        "domain_fields": domain_fields,
         "qa_pairs": qa,
     }
 
 
-def _call_openai_style_completion(base: str, api_key_header_name: str, api_key: str, model: str, system_prompt: str, user_content: str, extra_headers: Optional[Dict[str, str]] = None) -> Optional[str]:
-    headers = {api_key_header_name: api_key, "Content-Type": "application/json"}
-    if extra_headers:
-        headers.update(extra_headers)
-    body = {
-        "model": model,
-        "messages": [
-            {"role": "system", "content": system_prompt},
-            {"role": "user", "content": user_content},
-        ],
-        "temperature": 0.3,
-    }
-    resp = requests.post(base + '/v1/chat/completions', headers=headers, json=body, timeout=45)
-    resp.raise_for_status()
-    data = resp.json()
-    return data['choices'][0]['message']['content']
+# 提供商尝试顺序：Qwen(DashScope) -> DeepSeek -> OpenAI -> Anthropic，全部失败时使用启发式结果
+# 总结输入超出模型上下文时保留的最大字符数
+SUMMARY_TRUNCATE_CHARS = 6000
 
 
-def _summarize_with_qwen(enriched_text: str) -> Optional[Dict[str, Any]]:
-    _bootstrap_provider_env_from_toml()
-    api_key = os.getenv('DASHSCOPE_API_KEY') or os.getenv('QWEN_API_KEY')
-    if not api_key:
-        return None
-    try:
-        model = os.getenv('QWEN_SUMMARY_MODEL', os.getenv('QWEN_MODEL', 'qwen-plus'))
-        base = _normalize_api_base(os.getenv('QWEN_API_BASE') or 'https://dashscope.aliyuncs.com/compatible-mode', strip_v1=True)
-        logger.info(f"Clarification summary provider=Qwen(DashScope) model={model} base={base}")
-        system_prompt = (
-            "You are a product strategist. Summarize and refine the idea based on the given enriched idea (original idea + Q&A). "
-            "Return strict JSON with keys: title, refined_idea, user_segments[], core_pain_points[], key_features[], constraints[], success_metrics[], risks[], next_steps[]"
-        )
-        content = _call_openai_style_completion(base, 'Authorization', f'Bearer {api_key}', model, system_prompt, enriched_text)
-        obj = _extract_json(content or '')
-        return obj or {"refined_idea": (content or '').strip()}
-    except Exception as e:
-        logger.warning(f"Qwen summary failed: {e}")
+def _normalize_model_overrides(overrides: Optional[Dict[str, Any]]) -> Optional[Dict[str, Any]]:
+    """Validate per-session model overrides: { providers?: [name], models?: {provider: model} }."""
+    if not overrides:
         return None
+    providers = list(overrides.get("providers") or [])
+    models = dict(overrides.get("models") or {})
+    unknown = [p for p in [*providers, *models] if p not in PROVIDER_SETTINGS]
+    if unknown:
+        raise ValueError(f"unknown provider: {', '.join(unknown)}")
+    return {"providers": providers, "models": models}
+
+
+def _provider_order(overrides: Optional[Dict[str, Any]]) -> List[str]:
+    if (overrides or {}).get("providers"):
+        return overrides["providers"]
+    if mock_provider_selected():
+        return [MOCK_PROVIDER]
+    return [provider for provider in app_config.provider_order if provider in PROVIDER_SETTINGS]
 
 
-def _summarize_with_deepseek(enriched_text: str) -> Optional[Dict[str, Any]]:
-    _bootstrap_provider_env_from_toml()
-    api_key = os.getenv('DEEPSEEK_API_KEY')
-    if not api_key:
+def _configured_providers(overrides: Optional[Dict[str, Any]]) -> List[str]:
+    """Providers in trial order that can actually be called (have a key, or fixtures in replay mode)."""
+    return [provider for provider in _provider_order(overrides) if get_provider_settings(provider)]
+
+
+def _agent_model(agent: str, provider: str, settings: Dict[str, Any], overrides: Optional[Dict[str, Any]] = None) -> str:
+    if (overrides or {}).get("models", {}).get(provider):
+        return overrides["models"][provider]
+    params = app_config.get_agent_settings(agent)
+    if params.models.get(provider):
+        return params.models[provider]
+    if agent == "summarizer":
+        # 兼容 OPENAI_SUMMARY_MODEL 等总结专用模型环境变量
+        summary_env = PROVIDER_SETTINGS[provider]["model_env"].replace("_MODEL", "_SUMMARY_MODEL")
+        if os.getenv(summary_env):
+            return os.environ[summary_env]
+    return settings["model"]
+
+
+def _degrade(agent: str, fallback: Callable[[], Any], skipped: Any = None) -> Any:
+    """Apply the agent's degradation policy once every provider has failed.
+
+    Offline mode always uses the built-in rules, since no model call is attempted.
+    """
+    policy = app_config.get_agent_settings(agent).degradation
+    rules = app_config.offline or policy == "fallback_to_rules"
+    record_audit("agent", agent, success=rules, provider="rules" if rules else None,
+                 fallback=not app_config.offline, degradation=None if app_config.offline else policy)
+    if rules:
+        return fallback()
+    if policy == "fail_fast":
+        raise AppError(ErrorCode.AGENT_UNAVAILABLE, f"{agent} failed on every provider")
+    logger.warning(f"{agent} failed on every provider, skipped")
+    return skipped
+
+
+def _note_skipped(session: Dict[str, Any], agent: str) -> Dict[str, Any]:
+    """Record a step skipped by skip_with_warning on the session; returns the warning."""
+    warning = {"agent": agent, "message": f"{agent} skipped: no model response", "at": datetime.now().isoformat()}
+    session.setdefault("warnings", []).append(warning)
+    return warning
+
+
+def _agent_deadline() -> float:
+    """Monotonic deadline for one agent step, covering retries and provider fallback."""
+    return time.monotonic() + app_config.agent_timeout
+
+
+def _agent_chat(
+    agent: str,
+    provider: str,
+    variables: Dict[str, Any],
+    deadline: float,
+    overrides: Optional[Dict[str, Any]] = None,
+    fallback: bool = False,
+) -> Optional[str]:
+    """Call one provider with the agent's prompts and generation parameters in JSON output mode; None if the provider has no key.
+
+    fallback marks a call made after an earlier provider failed; it is recorded in the session trace
+    and announced with a system.warning event so the UI can show degraded-mode status.
+    """
+    settings = get_provider_settings(provider)
+    if not settings:
         return None
-    try:
-        model = os.getenv('DEEPSEEK_SUMMARY_MODEL', os.getenv('DEEPSEEK_MODEL', 'deepseek-chat'))
-        base = _normalize_api_base(os.getenv('DEEPSEEK_API_BASE') or 'https://api.deepseek.com', strip_v1=True)
-        logger.info(f"Clarification summary provider=DeepSeek model={model} base={base}")
-        system_prompt = (
-            "You are a product strategist. Summarize and refine the idea based on the given enriched idea (original idea + Q&A). "
-            "Return strict JSON with keys: title, refined_idea, user_segments[], core_pain_points[], key_features[], constraints[], success_metrics[], risks[], next_steps[]"
+    params = app_config.get_agent_settings(agent)
+    model = _agent_model(agent, provider, settings, overrides)
+    if fallback:
+        event_bus.emit(EventTypes.SYSTEM_WARNING, {
+            "fallback": True,
+            "agent": agent,
+            "provider": provider,
+            "model": model,
+            "session_id": get_log_context().get("session_id"),
+        }, source=agent)
+    with log_context(agent=agent, provider=provider):
+        logger.info(f"{agent} model={model} base={settings['base']}")
+        started, started_at = time.monotonic(), datetime.now().isoformat()
+        system_prompt = params.system_prompt or render_prompt(f"{agent}.system", variables)
+        user_prompt = render_prompt(f"{agent}.user", variables)
+        request = dict(
+            model=model,
+            temperature=params.temperature,
+            max_tokens=params.max_tokens,
+            top_p=params.top_p,
+            timeout=params.timeout or app_config.request_timeout,
+            json_mode=True,
+            deadline=deadline,
         )
-        content = _call_openai_style_completion(base, 'Authorization', f'Bearer {api_key}', model, system_prompt, enriched_text)
-        obj = _extract_json(content or '')
-        return obj or {"refined_idea": (content or '').strip()}
-    except Exception as e:
-        logger.warning(f"DeepSeek summary failed: {e}")
-        return None
+        try:
+            if app_config.enable_streaming:
+                content = _stream_agent_chat(agent, settings, system_prompt, user_prompt, request)
+            else:
+                content = chat_completion(settings, system_prompt, user_prompt, use_cache=params.cache, **request)
+        except Exception as e:
+            record_audit("agent", agent, success=False, provider=provider, model=model, fallback=fallback, started_at=started_at,
+                         duration_ms=int((time.monotonic() - started) * 1000), error_code=classify_exception(e).code.value)
+            raise
+        record_audit("agent", agent, provider=provider, model=model, fallback=fallback, started_at=started_at,
+                     duration_ms=int((time.monotonic() - started) * 1000))
+        return content
+
+
+def _stream_agent_chat(agent: str, settings: Dict[str, Any], system_prompt: str, user_prompt: str,
+                       request: Dict[str, Any]) -> str:
+    """Stream the reply to the UI as agent.output_chunk events.
+
+    If the stream fails before any text arrived, the same request is retried without streaming
+    (with the usual retries); a stream that breaks midway raises so the caller can fall back.
+    """
+    session_id = get_log_context().get("session_id")
+    emitted: List[str] = []
+
+    def emit(delta: str, done: bool = False) -> None:
+        event_bus.emit(EventTypes.AGENT_OUTPUT_CHUNK, {
+            "session_id": session_id,
+            "agent": agent,
+            "provider": settings["provider"],
+            "delta": delta,
+            "done": done,
+        }, source=agent)
 
+    def on_chunk(delta: str) -> None:
+        emitted.append(delta)
+        emit(delta)
 
-def _summarize_with_openai(enriched_text: str) -> Optional[Dict[str, Any]]:
-    _bootstrap_provider_env_from_toml()
-    api_key = os.getenv('OPENAI_API_KEY')
-    if not api_key:
-        return None
     try:
-        model = os.getenv('OPENAI_SUMMARY_MODEL', os.getenv('OPENAI_MODEL', 'gpt-4o-mini'))
-        base = _normalize_api_base(os.getenv('OPENAI_API_BASE') or 'https://api.openai.com', strip_v1=True)
-        logger.info(f"Clarification summary provider=OpenAI model={model} base={base}")
-        system_prompt = (
-            "You are a product strategist. Summarize and refine the idea based on the given enriched idea (original idea + Q&A). "
-            "Return strict JSON with keys: title, refined_idea, user_segments[], core_pain_points[], key_features[], constraints[], success_metrics[], risks[], next_steps[]"
-        )
-        content = _call_openai_style_completion(base, 'Authorization', f'Bearer {api_key}', model, system_prompt, enriched_text)
-        obj = _extract_json(content or '')
-        return obj or {"refined_idea": (content or '').strip()}
-    except Exception as e:
-        logger.warning(f"OpenAI summary failed: {e}")
-        return None
+        content = chat_completion_stream(settings, system_prompt, user_prompt, on_chunk, **request)
+    except ModelError as e:
+        if emitted:
+            emit("", done=True)
+            raise
+        logger.warning(f"{agent} streaming failed, retrying without streaming: {e}")
+        content = chat_completion(settings, system_prompt, user_prompt, **request)
+        emit(content)
+    emit("", done=True)
+    return content
+
+
+def _prompt_variables(seed: Dict[str, Any]) -> Dict[str, Any]:
+    template = get_domain_template(seed.get('domain'), seed.get('language'))
+    return {
+        "idea": sanitize_prompt_text(seed.get('raw_text', '')).text,
+        "domain": seed.get('domain') or 'unspecified',
+        "context_hints": sanitize_prompt_text(', '.join(str(h) for h in seed.get('context_hints') or [])).text or 'none',
+        "domain_slots": clarifier_guidance(template),
+        "domain_fields": summarizer_guidance(template),
+        "language_instruction": language_instruction(seed.get('language')),
+    }
 
 
-def _summarize_with_anthropic(enriched_text: str) -> Optional[Dict[str, Any]]:
-    _bootstrap_provider_env_from_toml()
-    api_key = os.getenv('ANTHROPIC_API_KEY')
-    if not api_key:
-        return None
+def _summarize_with(
+    provider: str,
+    variables: Dict[str, Any],
+    deadline: float,
+    overrides: Optional[Dict[str, Any]] = None,
+    fallback: bool = False,
+) -> Optional[Dict[str, Any]]:
     try:
-        model = os.getenv('ANTHROPIC_SUMMARY_MODEL', os.getenv('ANTHROPIC_MODEL', 'claude-3-5-sonnet-20240620'))
-        base = _normalize_api_base(os.getenv('ANTHROPIC_API_BASE') or 'https://api.anthropic.com', strip_v1=False)
-        logger.info(f"Clarification summary provider=Anthropic model={model} base={base}")
-        # Anthropic uses different headers
-        headers = {
-            'x-api-key': api_key,
-            'anthropic-version': '2023-06-01',
-            'content-type': 'application/json',
-        }
-        body = {
-            'model': model,
-            'max_tokens': 1200,
-            'messages': [
-                {"role": "user", "content": (
-                    "You are a product strategist. Summarize and refine the idea based on the given enriched idea (original idea + Q&A). "
-                    "Return strict JSON with keys: title, refined_idea, user_segments[], core_pain_points[], key_features[], constraints[], success_metrics[], risks[], next_steps[]\n\n"
-                    + enriched_text
-                )}
-            ]
-        }
-        resp = requests.post(base + '/v1/messages', headers=headers, json=body, timeout=60)
-        resp.raise_for_status()
-        data = resp.json()
-        content = ''.join(part.get('text', '') for part in data.get('content', []) if isinstance(part, dict))
+        try:
+            content = _agent_chat("summarizer", provider, variables, deadline, overrides, fallback)
+        except ModelError as e:
+            if e.kind != ModelErrorKind.CONTEXT_TOO_LONG:
+                raise
+            # 问答内容超出上下文时截断后用同一提供商再试一次
+            logger.warning(f"{PROVIDER_SETTINGS[provider]['label']} summary input too long, retrying truncated")
+            truncated = dict(variables, enriched_idea=variables["enriched_idea"][:SUMMARY_TRUNCATE_CHARS])
+            content = _agent_chat("summarizer", provider, truncated, deadline, overrides, fallback)
+        if content is None:
+            return None
         obj = _extract_json(content or '')
         return obj or {"refined_idea": (content or '').strip()}
     except Exception as e:
-        logger.warning(f"Anthropic summary failed: {e}")
+        logger.warning(f"{PROVIDER_SETTINGS[provider]['label']} summary failed: {e}")
         return None
 
 
-def _generate_summary(session: Dict[str, Any]) -> Dict[str, Any]:
-    enriched = _build_enriched_idea(session)
-    # Provider order: Qwen -> DeepSeek -> OpenAI -> Anthropic -> heuristic
-    summary = (
-        _summarize_with_qwen(enriched)
-        or _summarize_with_deepseek(enriched)
-        or _summarize_with_openai(enriched)
-        or _summarize_with_anthropic(enriched)
-    )
-    if summary:
-        logger.info("Clarification summary generated via LLM")
-        return summary
-    logger.info("Clarification summary fallback to heuristic")
-    return _heuristic_summary(session)
+def _generate_summary(session: Dict[str, Any]) -> Optional[Dict[str, Any]]:
+    """LLM summary; when every provider fails the summarizer's degradation policy decides (None = skipped)."""
+    variables = _prompt_variables(session.get('idea_seed') or {})
+    variables["enriched_idea"] = sanitize_prompt_text(_build_enriched_idea(session)).text
+    deadline = _agent_deadline()
+    overrides = session.get('model_overrides')
+    with log_context(session_id=session.get('id')):
+        for index, provider in enumerate(_configured_providers(overrides)):
+            if time.monotonic() >= deadline:
+                logger.warning("Clarification summary exceeded agent_timeout")
+                break
+            summary = _summarize_with(provider, variables, deadline, overrides, fallback=index > 0)
+            if summary:
+                logger.info("Clarification summary generated via LLM")
+                return summary
+    return _degrade("summarizer", lambda: _heuristic_summary(session))
 
 
 def _extract_json(text: str) -> Optional[dict]:
@@ -298,49 +490,6 @@ def _extract_json(text: str) -> Optional[dict]:
     return None
 
 
-def _normalize_api_base(base: str, strip_v1: bool = True) -> str:
-    if not base:
-        return base
-    b = base.strip().rstrip('/')
-    if strip_v1 and b.endswith('/v1'):
-        b = b[:-3]
-    return b
-
-
-def _bootstrap_provider_env_from_toml() -> None:
-    """If env vars are missing, try load from MAgent/config.toml and set os.environ accordingly.
-    Only sets variables that are currently missing.
-    """
-    try:
-        import tomllib  # Python 3.11+
-        # Locate config.toml under MAgent/
-        here = os.path.dirname(os.path.abspath(__file__))
-        # __file__ -> .../MAgent/src/api/questioning_api.py; go up to MAgent/
-        magent_dir = os.path.abspath(os.path.join(here, '..', '..'))
-        cfg_path = os.path.join(magent_dir, 'config.toml')
-        if not os.path.exists(cfg_path):
-            return
-        with open(cfg_path, 'rb') as f:
-            data = tomllib.load(f)
-        ai = data.get('ai', {}) if isinstance(data, dict) else {}
-        # Map keys -> env if missing
-        mapping: List[Tuple[str, str]] = [
-            ('OPENAI_API_KEY', ai.get('openai_api_key') or ''),
-            ('ANTHROPIC_API_KEY', ai.get('claude_api_key') or ''),
-            ('DEEPSEEK_API_KEY', ai.get('deepseek_api_key') or ''),
-            ('QWEN_API_KEY', ai.get('qwen_api_key') or ''),
-        ]
-        for env_name, value in mapping:
-            if value and not os.getenv(env_name):
-                os.environ[env_name] = value
-        # Normalize and set OPENAI_API_BASE if provided in toml
-        openai_base = ai.get('openai_base_url') or ''
-        if openai_base and not os.getenv('OPENAI_API_BASE'):
-            os.environ['OPENAI_API_BASE'] = _normalize_api_base(openai_base, strip_v1=True)
-    except Exception as e:
-        logger.debug(f"config.toml bootstrap skipped or failed: {e}")
-
-
 def _normalize_question(text: str) -> str:
     """Normalize question text for deduplication: trim, lowercase, remove common punctuations and extra spaces.
     Keep CJK characters; strip ASCII and CJK punctuations.
@@ -354,21 +503,30 @@ def _normalize_question(text: str) -> str:
     return s
 
 
-def _heuristic_questions(idea: str) -> List[ClarificationQuestion]:
-    base: List[ClarificationQuestion] = [
-        ClarificationQuestion(slot_name="target_user", question="这个想法的目标用户是谁？", priority=9, type="target"),
-        ClarificationQuestion(slot_name="core_pain", question="它要解决的核心痛点是什么？", priority=9, type="problem"),
-        ClarificationQuestion(slot_name="key_features", question="预期的核心功能模块有哪些？", priority=8, type="features"),
-        ClarificationQuestion(slot_name="data_sources", question="可用的数据来源或先决条件是什么？", priority=7, type="data"),
-        ClarificationQuestion(slot_name="success_metrics", question="成功的评估标准或关键指标是什么？", priority=7, type="metrics"),
-        ClarificationQuestion(slot_name="constraints", question="预算/时间/合规方面有无约束？", priority=6, type="constraints"),
+def _heuristic_questions(idea: str, domain: Optional[str] = None, language: Optional[str] = None) -> List[ClarificationQuestion]:
+    def fallback(slot_name: str, priority: int, type: str) -> ClarificationQuestion:
+        return ClarificationQuestion(slot_name=slot_name, question=t(f"fallback.question.{slot_name}", language), priority=priority, type=type)
+
+    template = get_domain_template(domain, language)
+    if template:
+        # 领域模板的问题库优先，其后补充通用问题
+        base: List[ClarificationQuestion] = [ClarificationQuestion(**slot) for slot in template["slots"]]
+    else:
+        base = []
+    base += [
+        fallback("target_user", 9, "target"),
+        fallback("core_pain", 9, "problem"),
+        fallback("key_features", 8, "features"),
+        fallback("data_sources", 7, "data"),
+        fallback("success_metrics", 7, "metrics"),
+        fallback("constraints", 6, "constraints"),
     ]
     idea_l = idea.lower()
     if any(k in idea_l for k in ["education", "learning", "学习", "教育"]):
-        base.insert(0, ClarificationQuestion(slot_name="education_stage", question="面向哪个学段/年龄层？", priority=10, type="domain"))
-        base.append(ClarificationQuestion(slot_name="personalization_basis", question="个性化策略基于哪些学习风格理论？", priority=7, type="method"))
+        base.insert(0, fallback("education_stage", 10, "domain"))
+        base.append(fallback("personalization_basis", 7, "method"))
     if any(k in idea_l for k in ["platform", "平台"]):
-        base.append(ClarificationQuestion(slot_name="business_model", question="平台的商业模式与收费方式是什么？", priority=7, type="business"))
+        base.append(fallback("business_model", 7, "business"))
     # Deduplicate slot names
     seen = set()
     uniq: List[ClarificationQuestion] = []
@@ -379,210 +537,137 @@ def _heuristic_questions(idea: str) -> List[ClarificationQuestion]:
     return uniq[:10]
 
 
-def _call_openai_for_questions(idea: str) -> Optional[List[ClarificationQuestion]]:
-    # Ensure env possibly loaded from toml
-    _bootstrap_provider_env_from_toml()
-    api_key = os.getenv('OPENAI_API_KEY')
-    if not api_key:
-        return None
-    try:
-        body = {
-            "model": os.getenv('OPENAI_MODEL', 'gpt-4o-mini'),
-            "messages": [
-                {"role": "system", "content": "You generate a concise list of 6-10 clarification questions for the idea. Return strict JSON with fields: questions:[{question, type, priority(1-10), slot_name}]"},
-                {"role": "user", "content": f"Idea: {idea}\nReturn JSON only."}
-            ],
-            "temperature": 0.3,
-        }
-        base = _normalize_api_base(os.getenv('OPENAI_API_BASE') or 'https://api.openai.com', strip_v1=True)
-        logger.info(f"Clarifier provider=OpenAI model={body['model']} base={base}")
-        resp = requests.post(
-            base + '/v1/chat/completions',
-            headers={"Authorization": f"Bearer {api_key}", "Content-Type": "application/json"},
-            json=body,
-            timeout=30,
-        )
-        resp.raise_for_status()
-        data = resp.json()
-        content = data['choices'][0]['message']['content']
-        obj = _extract_json(content) or {}
-        questions = obj.get('questions', [])
-        out: List[ClarificationQuestion] = []
-        for i, q in enumerate(questions):
-            slot = q.get('slot_name') or f"slot_{i}"
-            pri = int(q.get('priority', 7))
-            typ = q.get('type', 'general')
-            out.append(ClarificationQuestion(slot_name=slot, question=q.get('question', '').strip(), priority=max(1, min(10, pri)), type=typ))
-        return [q for q in out if q.question]
-    except Exception as e:
-        logger.warning(f"OpenAI question generation failed, fallback: {e}")
-        return None
-
-
-def _call_anthropic_for_questions(idea: str) -> Optional[List[ClarificationQuestion]]:
-    _bootstrap_provider_env_from_toml()
-    api_key = os.getenv('ANTHROPIC_API_KEY')
-    if not api_key:
-        return None
-    try:
-        body = {
-            "model": os.getenv('ANTHROPIC_MODEL', 'claude-3-5-sonnet-20240620'),
-            "max_tokens": 800,
-            "messages": [
-                {"role": "user", "content": f"You generate a concise list of 6-10 clarification questions for the idea, return JSON: {{\"questions\":[{{question,type,priority,slot_name}}]}}. Idea: {idea}"}
-            ]
-        }
-        base = _normalize_api_base(os.getenv('ANTHROPIC_API_BASE') or 'https://api.anthropic.com', strip_v1=False)
-        logger.info(f"Clarifier provider=Anthropic model={body['model']} base={base}")
-        resp = requests.post(
-            base + '/v1/messages',
-            headers={
-                "x-api-key": api_key,
-                "anthropic-version": "2023-06-01",
-                "content-type": "application/json",
-            },
-            json=body,
-            timeout=30,
-        )
-        resp.raise_for_status()
-        data = resp.json()
-        # Anthropic content can be array with text
-        content = ''.join(part.get('text', '') for part in data.get('content', []) if isinstance(part, dict))
-        obj = _extract_json(content) or {}
-        questions = obj.get('questions', [])
-        out: List[ClarificationQuestion] = []
-        for i, q in enumerate(questions):
-            slot = q.get('slot_name') or f"slot_{i}"
+def _parse_questions(content: str) -> List[ClarificationQuestion]:
+    obj = _extract_json(content) or {}
+    questions = obj.get('questions', [])
+    out: List[ClarificationQuestion] = []
+    for i, q in enumerate(questions):
+        slot = q.get('slot_name') or f"slot_{i}"
+        try:
             pri = int(q.get('priority', 7))
-            typ = q.get('type', 'general')
-            out.append(ClarificationQuestion(slot_name=slot, question=q.get('question', '').strip(), priority=max(1, min(10, pri)), type=typ))
-        return [q for q in out if q.question]
-    except Exception as e:
-        logger.warning(f"Anthropic question generation failed, fallback: {e}")
-        return None
-
-
-def _generate_questions(idea: str) -> List[ClarificationQuestion]:
-    # Try providers in order: Qwen(DashScope) -> DeepSeek -> OpenAI -> Anthropic -> heuristic
-    qs = (
-        _call_qwen_for_questions(idea)
-        or _call_deepseek_for_questions(idea)
-        or _call_openai_for_questions(idea)
-        or _call_anthropic_for_questions(idea)
-    )
-    if qs and len(qs) >= 4:
-        return qs
-    return _heuristic_questions(idea)
-
-
-def _call_deepseek_for_questions(idea: str) -> Optional[List[ClarificationQuestion]]:
-    """Use DeepSeek OpenAI-compatible API: https://api.deepseek.com/v1/chat/completions"""
-    _bootstrap_provider_env_from_toml()
-    api_key = os.getenv('DEEPSEEK_API_KEY')
-    if not api_key:
-        return None
+        except Exception:
+            pri = 7
+        typ = q.get('type', 'general')
+        out.append(ClarificationQuestion(slot_name=slot, question=q.get('question', '').strip(), priority=max(1, min(10, pri)), type=typ))
+    return [q for q in out if q.question]
+
+
+def _questions_with(
+    provider: str,
+    variables: Dict[str, Any],
+    deadline: float,
+    overrides: Optional[Dict[str, Any]] = None,
+    fallback: bool = False,
+) -> Optional[List[ClarificationQuestion]]:
     try:
-        model = os.getenv('DEEPSEEK_MODEL', 'deepseek-chat')
-        base = os.getenv('DEEPSEEK_API_BASE') or 'https://api.deepseek.com'
-        body = {
-            "model": model,
-            "messages": [
-                {
-                    "role": "system",
-                    "content": "You generate a concise list of 6-10 clarification questions for the idea. Return strict JSON with fields: questions:[{question, type, priority(1-10), slot_name}]",
-                },
-                {"role": "user", "content": f"Idea: {idea}\nReturn JSON only."},
-            ],
-            "temperature": 0.3,
-        }
-        base = _normalize_api_base(base, strip_v1=True) or 'https://api.deepseek.com'
-        logger.info(f"Clarifier provider=DeepSeek model={model} base={base}")
-        resp = requests.post(
-            base + '/v1/chat/completions',
-            headers={"Authorization": f"Bearer {api_key}", "Content-Type": "application/json"},
-            json=body,
-            timeout=30,
-        )
-        resp.raise_for_status()
-        data = resp.json()
-        content = data['choices'][0]['message']['content']
-        obj = _extract_json(content) or {}
-        questions = obj.get('questions', [])
-        out: List[ClarificationQuestion] = []
-        for i, q in enumerate(questions):
-            slot = q.get('slot_name') or f"slot_{i}"
-            try:
-                pri = int(q.get('priority', 7))
-            except Exception:
-                pri = 7
-            typ = q.get('type', 'general')
-            out.append(ClarificationQuestion(slot_name=slot, question=q.get('question', '').strip(), priority=max(1, min(10, pri)), type=typ))
-        return [q for q in out if q.question]
+        content = _agent_chat("clarifier", provider, variables, deadline, overrides, fallback)
+        if content is None:
+            return None
+        return _parse_questions(content)
     except Exception as e:
-        logger.warning(f"DeepSeek question generation failed, fallback: {e}")
+        logger.warning(f"{PROVIDER_SETTINGS[provider]['label']} question generation failed, fallback: {e}")
         return None
 
 
-def _call_qwen_for_questions(idea: str) -> Optional[List[ClarificationQuestion]]:
-    """Use DashScope Qwen OpenAI-compatible API: https://dashscope.aliyuncs.com/compatible-mode/v1/chat/completions
-    Requires DASHSCOPE_API_KEY (or QWEN_API_KEY).
-    """
-    _bootstrap_provider_env_from_toml()
-    api_key = os.getenv('DASHSCOPE_API_KEY') or os.getenv('QWEN_API_KEY')
-    if not api_key:
-        return None
-    try:
-        model = os.getenv('QWEN_MODEL', 'qwen-plus')  # 可根据需要改为 qwen2.5 具体型号
-        # 兼容模式基地址：拼接我们已有的 /v1/chat/completions
-        base = os.getenv('QWEN_API_BASE') or 'https://dashscope.aliyuncs.com/compatible-mode'
-        body = {
-            "model": model,
-            "messages": [
-                {
-                    "role": "system",
-                    "content": "You generate a concise list of 6-10 clarification questions for the idea. Return strict JSON with fields: questions:[{question, type, priority(1-10), slot_name}]",
-                },
-                {"role": "user", "content": f"Idea: {idea}\nReturn JSON only."},
-            ],
-            "temperature": 0.3,
-        }
-        base = _normalize_api_base(base, strip_v1=True) or 'https://dashscope.aliyuncs.com/compatible-mode'
-        logger.info(f"Clarifier provider=Qwen(DashScope) model={model} base={base}")
-        resp = requests.post(
-            base + '/v1/chat/completions',
-            headers={"Authorization": f"Bearer {api_key}", "Content-Type": "application/json"},
-            json=body,
-            timeout=30,
-        )
-        resp.raise_for_status()
-        data = resp.json()
-        # 兼容 OpenAI 格式
-        content = data['choices'][0]['message']['content']
-        obj = _extract_json(content) or {}
-        questions = obj.get('questions', [])
-        out: List[ClarificationQuestion] = []
-        for i, q in enumerate(questions):
-            slot = q.get('slot_name') or f"slot_{i}"
-            try:
-                pri = int(q.get('priority', 7))
-            except Exception:
-                pri = 7
-            typ = q.get('type', 'general')
-            out.append(ClarificationQuestion(slot_name=slot, question=q.get('question', '').strip(), priority=max(1, min(10, pri)), type=typ))
-        return [q for q in out if q.question]
-    except Exception as e:
-        logger.warning(f"Qwen(DashScope) question generation failed, fallback: {e}")
-        return None
+def _generate_questions(seed: Dict[str, Any], overrides: Optional[Dict[str, Any]] = None) -> List[ClarificationQuestion]:
+    variables = _prompt_variables(seed)
+    deadline = _agent_deadline()
+    for index, provider in enumerate(_configured_providers(overrides)):
+        if time.monotonic() >= deadline:
+            logger.warning("Question generation exceeded agent_timeout")
+            break
+        qs = _questions_with(provider, variables, deadline, overrides, fallback=index > 0)
+        if qs and len(qs) >= 4:
+            return qs
+    return _degrade("clarifier", lambda: _heuristic_questions(variables["idea"], seed.get("domain"), seed.get("language")), [])
+
+
+def _tidy_questions(questions: List[ClarificationQuestion]) -> List[ClarificationQuestion]:
+    """Normalize priorities, drop duplicate questions, make slot names unique and cap the list."""
+    # Fill missing slot_name and normalize priorities first
+    for idx, q in enumerate(questions):
+        if not q.slot_name:
+            q.slot_name = f"slot_{idx}"
+        try:
+            q.priority = max(1, min(10, int(q.priority)))
+        except Exception:
+            q.priority = 7
+
+    # Deduplicate by normalized question text; and ensure slot_name uniqueness
+    seen_texts: set[str] = set()
+    seen_slots: set[str] = set()
+    unique: List[ClarificationQuestion] = []
+    for q in questions:
+        norm = _normalize_question(q.question)
+        if not norm:
+            continue
+        if norm in seen_texts:
+            # skip exact/similar duplicate question
+            continue
+        # ensure unique slot name
+        base_slot = q.slot_name or "slot"
+        slot = base_slot
+        suffix = 1
+        while slot in seen_slots:
+            slot = f"{base_slot}_{suffix}"
+            suffix += 1
+        q.slot_name = slot
+        seen_texts.add(norm)
+        seen_slots.add(slot)
+        unique.append(q)
+
+    # Cap number of questions to a reasonable amount
+    return unique[:10]
+
+
+def _store_questions(mgr: ClarificationSessionManager, session_id: str, questions: List[ClarificationQuestion]) -> Optional[ClarificationQuestion]:
+    """Save questions on the session and post the first one to its message history."""
+    session = mgr.load(session_id)
+    assert session
+    mgr.set_questions(session, questions)
+    first = mgr.next_unanswered(session)
+    # 记录首个问题到会话消息历史
+    if first:
+        session = mgr.load(session_id) or {}
+        msgs = session.get('messages') or []
+        msgs.append({
+            "role": "bot",
+            "slot_name": first.slot_name,
+            "content": first.question,
+            "timestamp": datetime.now().isoformat(),
+        })
+        session['messages'] = msgs
+        mgr.save(session)
+    return first
 
 
-def api_start_clarification_session(seed: Dict[str, Any]) -> Dict[str, Any]:
+def api_start_clarification_session(seed: Dict[str, Any], model_overrides: Optional[Dict[str, Any]] = None) -> Dict[str, Any]:
     """Create a clarification session and generate questions using LLMs or fallback.
-    seed: { raw_text: str, context_hints:[], domain?: str }
+    seed: { raw_text: str, context_hints:[], domain?: str, source?: str, language?: str }
+    language defaults to the output_language setting and is kept on the seed so the session's
+    questions, summaries and reports stay in one language.
+    source (or a raw_text that is just a URL) is a web page or document (txt/md/html/pdf/docx)
+    whose text becomes raw_text, with its title and description added to context_hints.
+    model_overrides: { providers?: [name], models?: {provider: model} }, stored with the session
+    and used instead of the global provider order / agent models for this session only.
     """
     try:
-        idea = (seed or {}).get('raw_text') or ''
+        try:
+            seed = resolve_idea_seed(seed, app_config.request_timeout)
+        except IntakeError as e:
+            return error_response(e)
+        idea = seed.get('raw_text') or ''
         if not idea.strip():
-            return {"success": False, "error": "empty idea"}
+            return app_error(ErrorCode.INVALID_INPUT, "empty idea")
+        language = output_language(seed.get('language'))
+        if language:
+            seed['language'] = language
+        else:
+            seed.pop('language', None)
+        try:
+            model_overrides = _normalize_model_overrides(model_overrides)
+        except ValueError as e:
+            return error_response(e)
 
         # 记录当前可用的提供商密钥与基础地址，辅助诊断
         _bootstrap_provider_env_from_toml()
@@ -600,71 +685,29 @@ def api_start_clarification_session(seed: Dict[str, Any]) -> Dict[str, Any]:
         )
 
         mgr = ClarificationSessionManager()
-        session_id = mgr.create_session(seed)
-        questions = _generate_questions(idea)
-        if questions and len(questions) >= 4:
-            logger.info(f"Clarification questions generated via LLM: count={len(questions)}")
-        else:
-            logger.info("Clarification questions fallback to heuristic generator")
-        # Fill missing slot_name and normalize priorities first
-        for idx, q in enumerate(questions):
-            if not q.slot_name:
-                q.slot_name = f"slot_{idx}"
-            try:
-                q.priority = max(1, min(10, int(q.priority)))
-            except Exception:
-                q.priority = 7
-
-        # Deduplicate by normalized question text; and ensure slot_name uniqueness
-        seen_texts: set[str] = set()
-        seen_slots: set[str] = set()
-        unique: List[ClarificationQuestion] = []
-        for q in questions:
-            norm = _normalize_question(q.question)
-            if not norm:
-                continue
-            if norm in seen_texts:
-                # skip exact/similar duplicate question
-                continue
-            # ensure unique slot name
-            base_slot = q.slot_name or "slot"
-            slot = base_slot
-            suffix = 1
-            while slot in seen_slots:
-                slot = f"{base_slot}_{suffix}"
-                suffix += 1
-            q.slot_name = slot
-            seen_texts.add(norm)
-            seen_slots.add(slot)
-            unique.append(q)
-
-        # Cap number of questions to a reasonable amount
-        questions = unique[:10]
+        session_id = mgr.create_session(seed, model_overrides)
         session = mgr.load(session_id)
-        assert session
-        mgr.set_questions(session, questions)
-        first = mgr.next_unanswered(session)
-        # 记录首个问题到会话消息历史
-        if first:
-            session = mgr.load(session_id) or {}
-            msgs = session.get('messages') or []
-            msgs.append({
-                "role": "bot",
-                "slot_name": first.slot_name,
-                "content": first.question,
-                "timestamp": datetime.now().isoformat(),
-            })
-            session['messages'] = msgs
+        session["safety"] = check_idea("\n".join([idea, *[str(h) for h in seed.get('context_hints') or []]]))
+        mgr.save(session)
+        with log_context(session_id=session_id):
+            questions = _generate_questions(seed, model_overrides)
+        logger.info(f"Clarification questions ready: count={len(questions)}")
+        warnings = []
+        if not questions:
+            warnings.append(_note_skipped(session, "clarifier"))
             mgr.save(session)
+        questions = _tidy_questions(questions)
+        first = _store_questions(mgr, session_id, questions)
         return {
             "success": True,
             "session_id": session_id,
             "questions": [asdict(q) for q in questions],
             "next_question": asdict(first) if first else None,
+            "warnings": warnings,
         }
     except Exception as e:
         logger.error(f"start_clarification_session failed: {e}")
-        return {"success": False, "error": str(e)}
+        return error_response(e)
 
 
 def api_submit_clarification_answer(session_id: str, slot_name: str, answer: str) -> Dict[str, Any]:
@@ -672,7 +715,7 @@ def api_submit_clarification_answer(session_id: str, slot_name: str, answer: str
         mgr = ClarificationSessionManager()
         session = mgr.load(session_id)
         if not session:
-            return {"success": False, "error": "session not found"}
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
         mgr.submit_answer(session, slot_name, answer)
 
         # 记录用户回答到消息历史
@@ -710,7 +753,7 @@ def api_submit_clarification_answer(session_id: str, slot_name: str, answer: str
         }
     except Exception as e:
         logger.error(f"submit_clarification_answer failed: {e}")
-        return {"success": False, "error": str(e)}
+        return error_response(e, session_id=session_id)
 
 
 def api_get_clarification_status(session_id: str) -> Dict[str, Any]:
@@ -718,21 +761,27 @@ def api_get_clarification_status(session_id: str) -> Dict[str, Any]:
         mgr = ClarificationSessionManager()
         session = mgr.load(session_id)
         if not session:
-            return {"success": False, "error": "session not found"}
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
         return {"success": True, "data": session}
     except Exception as e:
         logger.error(f"get_clarification_status failed: {e}")
-        return {"success": False, "error": str(e)}
+        return error_response(e, session_id=session_id)
+
+
+def _session_language(session: Dict[str, Any]) -> Optional[str]:
+    """Language for handoff labels: the seed's language, else the output_language setting."""
+    return output_language(session.get('idea_seed', {}).get('language'))
 
 
 def _build_enriched_idea(session: Dict[str, Any]) -> str:
     seed = session.get('idea_seed', {})
     base = seed.get('raw_text', '')
+    language = _session_language(session)
     qa_lines = []
     for q in session.get('questions', []):
         if q.get('answer'):
-            qa_lines.append(f"- {q['question']}\n  答：{q['answer']}")
-    enriched = base + "\n\n澄清结果：\n" + "\n".join(qa_lines)
+            qa_lines.append(f"- {q['question']}\n  " + t("handoff.answer", language, answer=q['answer']))
+    enriched = base + "\n\n" + t("handoff.clarification", language) + "\n" + "\n".join(qa_lines)
     return enriched
 
 
@@ -745,15 +794,19 @@ def api_finish_clarification(session_id: str) -> Dict[str, Any]:
         mgr = ClarificationSessionManager()
         session = mgr.load(session_id)
         if not session:
-            return {"success": False, "error": "session not found"}
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
 
         # If not all answered, still allow finishing; build enriched and LLM summary
         enriched_idea = _build_enriched_idea(session)
         summary = _generate_summary(session)
-        session['summary'] = summary
+        if summary:
+            _store_summary(session, summary, "generated")
+            language = _session_language(session)
+            handoff_text = t("handoff.summary", language) + "\n" + _format_summary_text(summary, language) + "\n\n" + enriched_idea
+        else:
+            _note_skipped(session, "summarizer")
+            handoff_text = enriched_idea
         mgr.save(session)
-        summary_text = _format_summary_text(summary)
-        handoff_text = "【总结】\n" + summary_text + "\n\n" + enriched_idea
 
         # Create project if not exists
         if not session.get('project_id'):
@@ -764,7 +817,7 @@ def api_finish_clarification(session_id: str) -> Dict[str, Any]:
                 "domain": (session.get('idea_seed') or {}).get('domain') or 'general',
             })
             if not project_res.get('success'):
-                return {"success": False, "error": project_res.get('error', 'create project failed')}
+                return forward_error(project_res, 'create project failed', session_id)
             project_id = project_res.get('project_id') or project_res.get('data', {}).get('id')
             session['project_id'] = project_id
             mgr.save(session)
@@ -773,19 +826,18 @@ def api_finish_clarification(session_id: str) -> Dict[str, Any]:
 
         wf_res = api_start_workflow(project_id, handoff_text, 'balanced')
         if not wf_res.get('success'):
-            return {"success": False, "error": wf_res.get('error', 'start workflow failed')}
+            return forward_error(wf_res, 'start workflow failed', session_id)
 
         session['status'] = 'completed'
         # 尝试记录工作流会话ID
         workflow_session_id = wf_res.get('session_id') or wf_res.get('data', {}).get('session_id')
-        if workflow_session_id:
-            session['workflow_session_id'] = workflow_session_id
+        _record_handoff(session, workflow_session_id)
         mgr.save(session)
 
         return {"success": True, "workflow_session_id": workflow_session_id}
     except Exception as e:
         logger.error(f"finish_clarification failed: {e}")
-        return {"success": False, "error": str(e)}
+        return error_response(e, session_id=session_id)
 
 
 def api_submit_summary(session_id: str, summary: Dict[str, Any], restart: bool = False) -> Dict[str, Any]:
@@ -799,16 +851,17 @@ def api_submit_summary(session_id: str, summary: Dict[str, Any], restart: bool =
         mgr = ClarificationSessionManager()
         session = mgr.load(session_id)
         if not session:
-            return {"success": False, "error": "session not found"}
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
 
         # 保存用户编辑后的总结
-        session['summary'] = summary or {}
+        _store_summary(session, summary or {}, "edited")
         mgr.save(session)
 
         # 组装交接文本
         enriched_idea = _build_enriched_idea(session)
-        summary_text = _format_summary_text(summary or {})
-        handoff_text = "【总结】\n" + summary_text + "\n\n" + enriched_idea
+        language = _session_language(session)
+        summary_text = _format_summary_text(summary or {}, language)
+        handoff_text = t("handoff.summary", language) + "\n" + summary_text + "\n\n" + enriched_idea
 
         # 创建项目（若尚无）
         if not session.get('project_id'):
@@ -819,7 +872,7 @@ def api_submit_summary(session_id: str, summary: Dict[str, Any], restart: bool =
                 "domain": (session.get('idea_seed') or {}).get('domain') or 'general',
             })
             if not project_res.get('success'):
-                return {"success": False, "error": project_res.get('error', 'create project failed')}
+                return forward_error(project_res, 'create project failed', session_id)
             project_id = project_res.get('project_id') or project_res.get('data', {}).get('id')
             session['project_id'] = project_id
             mgr.save(session)
@@ -829,15 +882,438 @@ def api_submit_summary(session_id: str, summary: Dict[str, Any], restart: bool =
         # 启动新的工作流实例（即使已有旧的，也启动新会话，前端会按 wf 参数订阅对应会话）
         wf_res = api_start_workflow(project_id, handoff_text, 'balanced')
         if not wf_res.get('success'):
-            return {"success": False, "error": wf_res.get('error', 'start workflow failed')}
+            return forward_error(wf_res, 'start workflow failed', session_id)
 
         session['status'] = 'completed'
         workflow_session_id = wf_res.get('session_id') or wf_res.get('data', {}).get('session_id')
-        if workflow_session_id:
-            session['workflow_session_id'] = workflow_session_id
+        _record_handoff(session, workflow_session_id)
         mgr.save(session)
 
         return {"success": True, "workflow_session_id": workflow_session_id}
     except Exception as e:
         logger.error(f"submit_summary failed: {e}")
-        return {"success": False, "error": str(e)}
+        return error_response(e, session_id=session_id)
+
+
+def api_get_summary(session_id: str, version: Optional[int] = None) -> Dict[str, Any]:
+    """Return the stored summary (latest, or a given version) without rerunning the summarizer."""
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        history = session.get('summary_history') or []
+        if version is not None:
+            entry = next((h for h in history if h.get('version') == version), None)
+            if not entry:
+                return app_error(ErrorCode.NOT_FOUND, "summary version not found", session_id)
+        elif session.get('summary'):
+            # Sessions saved before summary_history existed only carry `summary`
+            current = next((h for h in history if h.get('version') == session.get('summary_version')), None)
+            entry = current or (history[-1] if history else {"version": 0, "source": "generated",
+                                                             "summary": session['summary'], "created_at": session.get('updated_at')})
+        else:
+            return app_error(ErrorCode.INVALID_STATE, "summary not generated", session_id)
+        return {
+            "success": True,
+            "data": {
+                **entry,
+                "session_id": session_id,
+                "current_version": session.get('summary_version'),
+                "versions": [{"version": h['version'], "source": h['source'], "created_at": h['created_at'],
+                              "superseded": bool(h.get('superseded'))} for h in history],
+            },
+        }
+    except Exception as e:
+        logger.error(f"get_summary failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_regenerate_summary(session_id: str) -> Dict[str, Any]:
+    """Run the summarizer again on the stored Q&A and save it as a new version (no workflow is started)."""
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        summary = _generate_summary(session)
+        if not summary:
+            warning = _note_skipped(session, "summarizer")
+            mgr.save(session)
+            return {"success": True, "data": {"version": None, "summary": None}, "warnings": [warning]}
+        version = _store_summary(session, summary, "generated")
+        invalidated = _invalidate_handoff(session)
+        mgr.save(session)
+        return {"success": True, "data": {"version": version, "summary": summary, "invalidated_workflows": invalidated}, "warnings": []}
+    except Exception as e:
+        logger.error(f"regenerate_summary failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_update_summary(session_id: str, changes: Dict[str, Any]) -> Dict[str, Any]:
+    """Correct fields of the current summary (e.g. user_segments, constraints) and save the result as a new version.
+
+    Only the given fields are replaced. A workflow started from an earlier version is marked stale
+    (workflow_stale) so the UI can offer to re-run it; nothing is restarted automatically.
+    """
+    try:
+        cleaned = _validate_summary_edit(changes)
+    except ValueError as e:
+        return app_error(ErrorCode.INVALID_INPUT, str(e), session_id)
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        if not session.get('summary'):
+            return app_error(ErrorCode.INVALID_STATE, "summary not generated", session_id)
+        summary = {**session['summary'], **cleaned}
+        if not (summary.get('title') or summary.get('refined_idea')):
+            return app_error(ErrorCode.INVALID_INPUT, "summary needs a title or refined_idea", session_id)
+
+        version = _store_summary(session, summary, "edited")
+        invalidated = _invalidate_handoff(session)
+        mgr.save(session)
+        return {
+            "success": True,
+            "data": {"version": version, "summary": summary, "updated": sorted(cleaned), "invalidated_workflows": invalidated},
+        }
+    except Exception as e:
+        logger.error(f"update_summary failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_rollback_summary(session_id: str, version: int) -> Dict[str, Any]:
+    """Make an earlier summary version current again; later versions stay in summary_history marked superseded.
+
+    The next edit or regeneration is numbered after the newest version, so history is never rewritten.
+    """
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        history = session.get('summary_history') or []
+        entry = next((h for h in history if h.get('version') == version), None)
+        if not entry:
+            return app_error(ErrorCode.NOT_FOUND, "summary version not found", session_id)
+
+        superseded = []
+        for h in history:
+            later = h['version'] > version
+            if later and not h.get('superseded'):
+                superseded.append(h['version'])
+            h['superseded'] = later
+        session['summary'] = entry['summary']
+        session['summary_version'] = version
+        invalidated = _invalidate_handoff(session)
+        mgr.save(session)
+        return {
+            "success": True,
+            "data": {"version": version, "summary": entry['summary'], "superseded": superseded, "invalidated_workflows": invalidated},
+        }
+    except Exception as e:
+        logger.error(f"rollback_summary failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def _normalize_tags(tags: Optional[List[str]]) -> List[str]:
+    return list(dict.fromkeys(str(tag).strip() for tag in tags or [] if str(tag).strip()))
+
+
+def api_list_sessions(tag: Optional[str] = None, folder: Optional[str] = None) -> Dict[str, Any]:
+    """List clarification sessions (newest first) for the history view.
+
+    `tag` keeps sessions carrying that tag; `folder` keeps sessions in that folder
+    ("" selects sessions without a folder).
+    """
+    try:
+        mgr = ClarificationSessionManager()
+        sessions = [_session_overview(s) for s in mgr.storage.list_sessions() if s.get("id")]
+        if tag:
+            sessions = [s for s in sessions if tag in s["tags"]]
+        if folder is not None:
+            sessions = [s for s in sessions if (s["folder"] or "") == folder.strip()]
+        sessions.sort(key=lambda x: x.get("updated_at") or "", reverse=True)
+        return {"success": True, "data": sessions}
+    except Exception as e:
+        logger.error(f"list_sessions failed: {e}")
+        return error_response(e)
+
+
+def api_set_session_tags(session_id: str, tags: List[str]) -> Dict[str, Any]:
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        session["tags"] = _normalize_tags(tags)
+        mgr.save(session)
+        return {"success": True, "data": _session_overview(session)}
+    except Exception as e:
+        logger.error(f"set_session_tags failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_set_session_folder(session_id: str, folder: Optional[str]) -> Dict[str, Any]:
+    """Move a session into a folder; an empty folder name moves it back to the top level."""
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        session["folder"] = (folder or "").strip() or None
+        mgr.save(session)
+        return {"success": True, "data": _session_overview(session)}
+    except Exception as e:
+        logger.error(f"set_session_folder failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_list_tags() -> Dict[str, Any]:
+    """Tags and folders in use, with the number of sessions for each."""
+    try:
+        tags: Dict[str, int] = {}
+        folders: Dict[str, int] = {}
+        for session in ClarificationSessionManager().storage.list_sessions():
+            if not session.get("id"):
+                continue
+            for tag in session.get("tags") or []:
+                tags[tag] = tags.get(tag, 0) + 1
+            if session.get("folder"):
+                folders[session["folder"]] = folders.get(session["folder"], 0) + 1
+        return {
+            "success": True,
+            "data": {
+                "tags": [{"name": k, "count": tags[k]} for k in sorted(tags)],
+                "folders": [{"name": k, "count": folders[k]} for k in sorted(folders)],
+            },
+        }
+    except Exception as e:
+        logger.error(f"list_tags failed: {e}")
+        return error_response(e)
+
+
+def api_search_sessions(query: str, limit: int = 20) -> Dict[str, Any]:
+    """Keyword search over idea text, answers and summaries; ranked hits with snippets."""
+    try:
+        hits = search_sessions(query, limit)
+        return {
+            "success": True,
+            "data": [{**_session_overview(session), **result} for session, result in hits],
+        }
+    except Exception as e:
+        logger.error(f"search_sessions failed: {e}")
+        return error_response(e)
+
+
+def api_get_analytics(weeks: int = 12) -> Dict[str, Any]:
+    """Aggregate statistics over stored clarification sessions for the dashboard."""
+    try:
+        mgr = ClarificationSessionManager()
+        sessions = [s for s in mgr.storage.list_sessions() if s.get("id")]
+
+        per_week: Dict[str, int] = {}
+        domains: Dict[str, int] = {}
+        question_counts: List[int] = []
+        answered_counts: List[int] = []
+        summary_versions: List[int] = []
+        edited = 0
+        for session in sessions:
+            try:
+                created = datetime.fromisoformat(session.get("created_at") or "")
+            except ValueError:
+                created = None
+            if created:
+                year, week, _ = created.isocalendar()
+                key = f"{year}-W{week:02d}"
+                per_week[key] = per_week.get(key, 0) + 1
+            domain = (session.get("idea_seed") or {}).get("domain") or "general"
+            domains[domain] = domains.get(domain, 0) + 1
+            questions = session.get("questions", [])
+            question_counts.append(len(questions))
+            answered_counts.append(len([1 for q in questions if q.get("answer")]))
+            history = session.get("summary_history") or []
+            if history or session.get("summary"):
+                summary_versions.append(len(history) or 1)
+            if any(h.get("source") == "edited" for h in history):
+                edited += 1
+
+        def _avg(values: List[int]) -> Optional[float]:
+            return round(sum(values) / len(values), 2) if values else None
+
+        total = len(sessions)
+        completed = len([1 for s in sessions if s.get("status") == "completed"])
+        total_questions = sum(question_counts)
+        return {
+            "success": True,
+            "data": {
+                "total_sessions": total,
+                "completed_sessions": completed,
+                "completion_rate": round(completed / total, 4) if total else None,
+                "sessions_per_week": [{"week": k, "count": per_week[k]} for k in sorted(per_week)[-max(1, weeks):]],
+                "domains": dict(sorted(domains.items(), key=lambda kv: kv[1], reverse=True)),
+                "avg_questions": _avg(question_counts),
+                "avg_answered": _avg(answered_counts),
+                "answer_rate": round(sum(answered_counts) / total_questions, 4) if total_questions else None,
+                "sessions_with_summary": len(summary_versions),
+                "avg_summary_versions": _avg(summary_versions),
+                "summaries_edited": edited,
+            },
+        }
+    except Exception as e:
+        logger.error(f"get_analytics failed: {e}")
+        return error_response(e)
+
+
+def api_delete_session(session_id: str) -> Dict[str, Any]:
+    try:
+        mgr = ClarificationSessionManager()
+        if not mgr.load(session_id):
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        if not mgr.storage.delete_session(session_id):
+            return app_error(ErrorCode.STORAGE, "delete session failed", session_id)
+        set_confidential_terms(session_id, [])
+        return {"success": True}
+    except Exception as e:
+        logger.error(f"delete_session failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_rename_session(session_id: str, title: str) -> Dict[str, Any]:
+    try:
+        title = (title or "").strip()
+        if not title:
+            return app_error(ErrorCode.INVALID_INPUT, "empty title", session_id)
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        session["title"] = title
+        mgr.save(session)
+        return {"success": True, "data": _session_overview(session)}
+    except Exception as e:
+        logger.error(f"rename_session failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_set_confidential_terms(session_id: str, terms: List[str]) -> Dict[str, Any]:
+    """Set the session's confidential terms, masked in logs, cached responses and exports."""
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        cleaned = list(dict.fromkeys(str(term).strip() for term in terms or [] if str(term).strip()))
+        session["confidential_terms"] = cleaned
+        mgr.save(session)
+        set_confidential_terms(session_id, cleaned)
+        return {"success": True, "data": cleaned}
+    except Exception as e:
+        logger.error(f"set_confidential_terms failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_get_confidential_terms(session_id: str) -> Dict[str, Any]:
+    try:
+        session = ClarificationSessionManager().load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        return {"success": True, "data": session.get("confidential_terms") or []}
+    except Exception as e:
+        logger.error(f"get_confidential_terms failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_duplicate_session(session_id: str) -> Dict[str, Any]:
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        copy = mgr.duplicate(session)
+        set_confidential_terms(copy["id"], copy.get("confidential_terms") or [])
+        return {"success": True, "session_id": copy["id"], "data": _session_overview(copy)}
+    except Exception as e:
+        logger.error(f"duplicate_session failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def recover_interrupted_sessions() -> List[str]:
+    """Mark sessions left without questions by a crash during start as interrupted; returns their ids.
+
+    A successful start stores at least the heuristic questions unless the clarifier was skipped
+    (recorded in warnings), so a running session with neither can only come from a process that
+    died mid-generation. Call once at startup.
+    """
+    mgr = ClarificationSessionManager()
+    interrupted = []
+    for session in mgr.storage.list_sessions():
+        skipped = any(w.get("agent") == "clarifier" for w in session.get("warnings") or [])
+        if session.get("id") and session.get("status") == "running" and not session.get("questions") and not skipped:
+            session["status"] = "interrupted"
+            session["interrupted_at"] = datetime.now().isoformat()
+            mgr.save(session)
+            interrupted.append(session["id"])
+    if interrupted:
+        logger.warning(f"Marked {len(interrupted)} clarification session(s) as interrupted: {', '.join(interrupted)}")
+    return interrupted
+
+
+def api_recover_session(session_id: str) -> Dict[str, Any]:
+    """Resume an interrupted session from its stored idea seed by generating its questions again."""
+    try:
+        mgr = ClarificationSessionManager()
+        session = mgr.load(session_id)
+        if not session:
+            return app_error(ErrorCode.NOT_FOUND, "session not found", session_id)
+        if session.get("status") != "interrupted":
+            return app_error(ErrorCode.INVALID_STATE, "session is not interrupted", session_id)
+
+        with log_context(session_id=session_id):
+            questions = _tidy_questions(_generate_questions(session.get("idea_seed") or {}, session.get("model_overrides")))
+        session["status"] = "running"
+        session.pop("interrupted_at", None)
+        warnings = [_note_skipped(session, "clarifier")] if not questions else []
+        mgr.save(session)
+        first = _store_questions(mgr, session_id, questions)
+        logger.info(f"Recovered interrupted clarification session: {session_id}")
+        return {
+            "success": True,
+            "session_id": session_id,
+            "questions": [asdict(q) for q in questions],
+            "next_question": asdict(first) if first else None,
+            "warnings": warnings,
+        }
+    except Exception as e:
+        logger.error(f"recover_session failed: {e}")
+        return error_response(e, session_id=session_id)
+
+
+def api_list_domain_templates(language: Optional[str] = None) -> Dict[str, Any]:
+    """List domain templates selectable through the idea seed's domain, labelled in the output language."""
+    try:
+        return {"success": True, "data": list_domain_templates(output_language(language))}
+    except Exception as e:
+        logger.error(f"list_domain_templates failed: {e}")
+        return error_response(e)
+
+
+def api_list_prompts() -> Dict[str, Any]:
+    """List prompt templates with their override file paths."""
+    try:
+        return {"success": True, "data": list_prompts()}
+    except Exception as e:
+        logger.error(f"list_prompts failed: {e}")
+        return error_response(e)
+
+
+def api_reload_prompts() -> Dict[str, Any]:
+    """Re-read prompt override files from the data directory."""
+    try:
+        overrides = load_prompt_overrides()
+        logger.info(f"Prompt templates reloaded: overrides={sorted(overrides)}")
+        return {"success": True, "data": list_prompts()}
+    except Exception as e:
+        logger.error(f"reload_prompts failed: {e}")
+        return error_response(e)
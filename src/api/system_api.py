@@ -0,0 +1,212 @@
+"""
+System API
+"""
+
+import os
+import subprocess
+import sys
+from typing import Dict, Any, Optional
+
+from ..utils.logger import get_logger, configure_logging
+from ..utils.storage import CacheStorage
+from ..utils.ratelimit import rate_limit_stats
+from ..utils.health import get_system_health
+from ..utils.audit import AUDIT_KINDS, read_audit_log, record_audit, mask_secret, summarize_value
+from ..utils.errors import ErrorCode, app_error, error_response
+from ..utils.providers import MOCK_PROVIDER, PROVIDER_SETTINGS
+from config.app_config import app_config, get_storage_paths, PERSISTED_FIELDS, API_KEY_ENV_NAMES
+
+logger = get_logger(__name__)
+
+
+def api_get_storage_paths() -> Dict[str, Any]:
+    """获取数据、配置、缓存与日志的存储位置"""
+    try:
+        return {
+            "success": True,
+            "data": get_storage_paths()
+        }
+        
+    except Exception as e:
+        logger.error(f"获取存储位置失败: {e}")
+        return error_response(e, f"获取存储位置时发生错误: {str(e)}")
+
+
+def api_open_data_directory() -> Dict[str, Any]:
+    """在系统文件管理器中打开数据目录"""
+    try:
+        data_dir = app_config.data_dir.resolve()
+        data_dir.mkdir(parents=True, exist_ok=True)
+        
+        if sys.platform == "win32":
+            os.startfile(str(data_dir))  # type: ignore[attr-defined]
+        elif sys.platform == "darwin":
+            subprocess.Popen(["open", str(data_dir)])
+        else:
+            subprocess.Popen(["xdg-open", str(data_dir)])
+        
+        logger.info(f"已打开数据目录: {data_dir}")
+        
+        return {
+            "success": True,
+            "data": {"path": str(data_dir)}
+        }
+        
+    except Exception as e:
+        logger.error(f"打开数据目录失败: {e}")
+        return error_response(e, f"打开数据目录时发生错误: {str(e)}")
+
+
+def api_save_config(settings: Dict[str, Any]) -> Dict[str, Any]:
+    """更新并保存用户设置，重启后仍然生效
+
+    注意：通过界面保存的 API 密钥以明文写入 settings.json（文件权限为 0600）；
+    不希望密钥落盘时请改用环境变量。
+    """
+    try:
+        order = (settings or {}).get("provider_order")
+        if isinstance(order, list):
+            unknown = [name for name in order if name not in PROVIDER_SETTINGS or name == MOCK_PROVIDER]
+            if unknown:
+                return app_error(ErrorCode.INVALID_INPUT, f"不支持的提供商: {', '.join(map(str, unknown))}")
+        app_config.apply_settings(settings or {})
+        
+        # 新填写的密钥立即对提供商请求生效
+        for field_name, env_name in API_KEY_ENV_NAMES.items():
+            if field_name in (settings or {}):
+                value = getattr(app_config, field_name)
+                if value:
+                    os.environ[env_name] = value
+                else:
+                    os.environ.pop(env_name, None)
+        
+        # 日志设置立即生效
+        if any(name.startswith("log_") for name in (settings or {})):
+            configure_logging(force=True)
+        
+        settings_file = app_config.save_settings()
+        logger.info(f"配置已保存: {settings_file}")
+        
+        # 审计：密钥只记录遮盖后的值
+        changed = settings or {}
+        record_audit("config", "save", fields=sorted(changed), values=summarize_value(
+            {name: value for name, value in changed.items() if name not in API_KEY_ENV_NAMES}))
+        for field_name in API_KEY_ENV_NAMES:
+            if field_name in changed:
+                value = getattr(app_config, field_name)
+                record_audit("key", "set" if value else "clear", field=field_name, value=mask_secret(value))
+        
+        return {
+            "success": True,
+            "data": {
+                "path": str(settings_file),
+                "updated": sorted((settings or {}).keys())
+            }
+        }
+        
+    except ValueError as e:
+        return error_response(e, f"配置无效: {str(e)}")
+    except Exception as e:
+        logger.error(f"保存配置失败: {e}")
+        return error_response(e, f"保存配置时发生错误: {str(e)}")
+
+
+def api_get_config() -> Dict[str, Any]:
+    """获取可编辑的用户设置（密钥仅返回是否已配置）"""
+    try:
+        settings = app_config.model_dump(mode="json", include=set(PERSISTED_FIELDS))
+        for field_name in API_KEY_ENV_NAMES:
+            settings[field_name] = bool(settings.get(field_name))
+        for endpoint in settings.get("model_endpoints", {}).values():
+            endpoint["api_key"] = bool(endpoint.get("api_key"))
+        
+        return {
+            "success": True,
+            "data": settings
+        }
+        
+    except Exception as e:
+        logger.error(f"获取配置失败: {e}")
+        return error_response(e, f"获取配置时发生错误: {str(e)}")
+
+
+def api_get_cache_stats() -> Dict[str, Any]:
+    """获取缓存条目数、占用空间与命中/未命中统计"""
+    try:
+        return {
+            "success": True,
+            "data": CacheStorage().stats()
+        }
+        
+    except Exception as e:
+        logger.error(f"获取缓存统计失败: {e}")
+        return error_response(e, f"获取缓存统计时发生错误: {str(e)}")
+
+
+def api_get_system_health(refresh: bool = False) -> Dict[str, Any]:
+    """获取模型提供商与存储的健康状态（延迟与错误信息）及可用功能"""
+    try:
+        return {
+            "success": True,
+            "data": get_system_health(refresh)
+        }
+        
+    except Exception as e:
+        logger.error(f"获取健康状态失败: {e}")
+        return error_response(e, f"获取健康状态时发生错误: {str(e)}")
+
+
+def api_get_rate_limit_stats() -> Dict[str, Any]:
+    """获取各提供商的限流窗口用量、排队数与累计等待时间"""
+    try:
+        return {
+            "success": True,
+            "data": rate_limit_stats()
+        }
+        
+    except Exception as e:
+        logger.error(f"获取限流统计失败: {e}")
+        return error_response(e, f"获取限流统计时发生错误: {str(e)}")
+
+
+def api_clear_cache() -> Dict[str, Any]:
+    """清空缓存目录"""
+    try:
+        cleared = CacheStorage().clear()
+        return {
+            "success": True,
+            "data": {"cleared": cleared}
+        }
+        
+    except Exception as e:
+        logger.error(f"清空缓存失败: {e}")
+        return error_response(e, f"清空缓存时发生错误: {str(e)}")
+
+
+def api_get_audit_log(
+    kind: Optional[str] = None,
+    action: Optional[str] = None,
+    session_id: Optional[str] = None,
+    since: Optional[str] = None,
+    until: Optional[str] = None,
+    success: Optional[bool] = None,
+    limit: int = 200,
+) -> Dict[str, Any]:
+    """获取审计日志（最新的在前），可按类型、操作名、会话、时间范围与是否成功筛选"""
+    try:
+        if kind and kind not in AUDIT_KINDS:
+            return app_error(ErrorCode.INVALID_INPUT, f"不支持的记录类型: {kind}（可选: {', '.join(AUDIT_KINDS)}）")
+        
+        entries = read_audit_log(kind, action, session_id, since, until, success, limit)
+        return {
+            "success": True,
+            "data": {
+                "entries": entries,
+                "count": len(entries),
+                "enabled": app_config.audit_log_enabled
+            }
+        }
+        
+    except Exception as e:
+        logger.error(f"获取审计日志失败: {e}")
+        return error_response(e, f"获取审计日志时发生错误: {str(e)}", session_id=session_id)
@@ -3,22 +3,50 @@ Workflow Management API
 """
 
 import asyncio
-from typing import Dict, Any
-from datetime import datetime
+import json
+import threading
+import time
+from concurrent.futures import ThreadPoolExecutor
+from pathlib import Path
+from typing import Dict, Any, List, Optional
+from datetime import datetime, timedelta
 
 from ..utils.logger import get_logger
 from ..utils.event_bus import event_bus, EventTypes
+from ..utils.safety import confidential_terms, redact_data
+from ..utils.audit import read_audit_log, record_audit
+from ..utils.storage import SessionStorage, is_valid_storage_id
+from ..utils.errors import ErrorCode, app_error, error_response
+from config.app_config import app_config
 
 logger = get_logger(__name__)
 
 # 简化的工作流状态存储
 workflow_sessions = {}
 
+# 工作流任务队列：最多 max_concurrent_workflows 个同时运行，其余按提交顺序等待
+_workflow_executor: Optional[ThreadPoolExecutor] = None
+_workflow_queue: List[str] = []
+_running_workflows = 0
+_queue_lock = threading.Lock()
+
+# 会话时间线包含的审计记录类型
+TRACE_KINDS = ("command", "stage", "agent", "model_call")
+
+# 各阶段对应的智能体（用于进度展示）
+STAGE_AGENTS = {
+    "questioning": "clarifier",
+    "iterating": "innovator",
+    "verifying": "verifier",
+    "summarizing": "summarizer",
+    "completed": None,
+}
+
 
 def api_start_workflow(project_id: str, initial_idea: str, workflow_mode: str = "balanced") -> Dict[str, Any]:
     """启动工作流"""
     try:
-        session_id = f"session_{datetime.now().strftime('%Y%m%d_%H%M%S')}_{project_id[:8]}"
+        session_id = f"session_{datetime.now().strftime('%Y%m%d_%H%M%S_%f')}_{project_id[:8]}"
         
         # 创建工作流会话
         session = {
@@ -28,10 +56,11 @@ def api_start_workflow(project_id: str, initial_idea: str, workflow_mode: str =
             "workflow_mode": workflow_mode,
             "stage": "questioning",
             "progress": 0.0,
-            "status": "running",
+            "status": "queued",
             "created_at": datetime.now().isoformat(),
             "messages": [],
-            "results": {}
+            "results": {},
+            "progress_detail": None
         }
         
         workflow_sessions[session_id] = session
@@ -43,22 +72,10 @@ def api_start_workflow(project_id: str, initial_idea: str, workflow_mode: str =
             "initial_idea": initial_idea
         })
         
-        # 模拟工作流进度（在后台线程中运行）
-        import threading
-        def run_simulation():
-            import asyncio
-            try:
-                loop = asyncio.new_event_loop()
-                asyncio.set_event_loop(loop)
-                loop.run_until_complete(simulate_workflow_progress(session_id))
-                loop.close()
-            except Exception as e:
-                logger.error(f"工作流模拟失败: {e}")
-
-        thread = threading.Thread(target=run_simulation, daemon=True)
-        thread.start()
+        # 模拟工作流进度（排队后在工作线程中运行）
+        position = _enqueue_workflow(session_id)
         
-        logger.info(f"工作流启动成功: {session_id}")
+        logger.info(f"工作流启动成功: {session_id}（队列位置 {position}）")
         
         return {
             "success": True,
@@ -66,26 +83,77 @@ def api_start_workflow(project_id: str, initial_idea: str, workflow_mode: str =
             "data": {
                 "session_id": session_id,
                 "status": "started",
-                "stage": "questioning"
+                "stage": "questioning",
+                "queue_position": position
             }
         }
         
     except Exception as e:
         logger.error(f"启动工作流失败: {e}")
-        return {
-            "success": False,
-            "error": f"启动工作流时发生错误: {str(e)}"
-        }
+        return error_response(e, f"启动工作流时发生错误: {str(e)}")
+
+
+def _get_executor() -> ThreadPoolExecutor:
+    global _workflow_executor
+    if _workflow_executor is None:
+        _workflow_executor = ThreadPoolExecutor(
+            max_workers=max(1, app_config.max_concurrent_workflows),
+            thread_name_prefix="workflow"
+        )
+    return _workflow_executor
+
+
+def _waiting_ahead(index: int) -> Optional[int]:
+    """队列中第 index 个任务前面仍需等待的任务数；空闲的工作线程足够时立即开始，返回 None（调用方需持有 _queue_lock）"""
+    idle = max(1, app_config.max_concurrent_workflows) - _running_workflows
+    position = index - max(idle, 0)
+    return position if position >= 0 else None
+
+
+def _enqueue_workflow(session_id: str) -> Optional[int]:
+    """提交工作流到队列，返回前面仍在等待的任务数；会立即开始时返回 None"""
+    with _queue_lock:
+        _workflow_queue.append(session_id)
+        position = _waiting_ahead(len(_workflow_queue) - 1)
+    _get_executor().submit(_run_workflow, session_id)
+    return position
+
+
+def _queue_position(session_id: str) -> Optional[int]:
+    """排队中的工作流前面还有几个任务；未排队或即将开始时返回 None"""
+    with _queue_lock:
+        if session_id not in _workflow_queue:
+            return None
+        return _waiting_ahead(_workflow_queue.index(session_id))
+
+
+def _run_workflow(session_id: str) -> None:
+    global _running_workflows
+    with _queue_lock:
+        if session_id in _workflow_queue:
+            _workflow_queue.remove(session_id)
+        session = workflow_sessions.get(session_id)
+        if not session or session["status"] != "queued":
+            return
+        session["status"] = "running"
+        _running_workflows += 1
+    try:
+        loop = asyncio.new_event_loop()
+        asyncio.set_event_loop(loop)
+        loop.run_until_complete(simulate_workflow_progress(session_id))
+        loop.close()
+    except Exception as e:
+        logger.error(f"工作流模拟失败: {e}")
+    finally:
+        with _queue_lock:
+            _running_workflows -= 1
 
 
 def api_get_workflow_status(session_id: str) -> Dict[str, Any]:
     """获取工作流状态"""
     try:
         if session_id not in workflow_sessions:
-            return {
-                "success": False,
-                "error": "工作流会话不存在"
-            }
+            return app_error(ErrorCode.NOT_FOUND, "工作流会话不存在", session_id)
         
         session = workflow_sessions[session_id]
         
@@ -96,6 +164,7 @@ def api_get_workflow_status(session_id: str) -> Dict[str, Any]:
                 "stage": session["stage"],
                 "progress": session["progress"],
                 "status": session["status"],
+                "queue_position": _queue_position(session_id),
                 "messages": session["messages"][-5:],  # 最近5条消息
                 "results": session["results"]
             }
@@ -103,22 +172,92 @@ def api_get_workflow_status(session_id: str) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"获取工作流状态失败: {e}")
+        return error_response(e, f"获取工作流状态时发生错误: {str(e)}", session_id=session_id)
+
+
+def api_get_session_progress(session_id: str) -> Dict[str, Any]:
+    """获取工作流最近一次的阶段进度（前端刷新后用于恢复进度视图）"""
+    try:
+        if session_id not in workflow_sessions:
+            return app_error(ErrorCode.NOT_FOUND, "工作流会话不存在", session_id)
+        
+        session = workflow_sessions[session_id]
+        detail = session.get("progress_detail") or {
+            "session_id": session_id,
+            "stage": session["stage"],
+            "progress": session["progress"],
+        }
+        started_at = datetime.fromisoformat(session["created_at"])
+        
+        return {
+            "success": True,
+            "data": {
+                **detail,
+                "status": session["status"],
+                "elapsed_ms": int((datetime.now() - started_at).total_seconds() * 1000)
+            }
+        }
+        
+    except Exception as e:
+        logger.error(f"获取工作流进度失败: {e}")
+        return error_response(e, f"获取工作流进度时发生错误: {str(e)}", session_id=session_id)
+
+
+def api_list_running_jobs() -> Dict[str, Any]:
+    """列出运行中、已暂停与排队中的工作流（排队的按队列顺序排在最后）"""
+    try:
+        with _queue_lock:
+            queued = [(session_id, _waiting_ahead(index)) for index, session_id in enumerate(_workflow_queue)]
+        jobs = [
+            {
+                "session_id": session_id,
+                "project_id": session["project_id"],
+                "status": session["status"],
+                "stage": session["stage"],
+                "progress": session["progress"],
+                "queue_position": None,
+                "created_at": session["created_at"]
+            }
+            for session_id, session in workflow_sessions.items()
+            if session["status"] in ("running", "paused")
+        ]
+        for session_id, position in queued:
+            session = workflow_sessions.get(session_id)
+            if session and session["status"] == "queued":
+                jobs.append({
+                    "session_id": session_id,
+                    "project_id": session["project_id"],
+                    "status": "queued",
+                    "stage": session["stage"],
+                    "progress": session["progress"],
+                    "queue_position": position,
+                    "created_at": session["created_at"]
+                })
+        
         return {
-            "success": False,
-            "error": f"获取工作流状态时发生错误: {str(e)}"
+            "success": True,
+            "data": {
+                "jobs": jobs,
+                "max_concurrent": max(1, app_config.max_concurrent_workflows)
+            }
         }
+        
+    except Exception as e:
+        logger.error(f"列出工作流任务失败: {e}")
+        return error_response(e, f"列出工作流任务时发生错误: {str(e)}")
 
 
 def api_pause_workflow(session_id: str) -> Dict[str, Any]:
     """暂停工作流"""
     try:
         if session_id not in workflow_sessions:
-            return {
-                "success": False,
-                "error": "工作流会话不存在"
-            }
+            return app_error(ErrorCode.NOT_FOUND, "工作流会话不存在", session_id)
         
-        workflow_sessions[session_id]["status"] = "paused"
+        # 排队中的任务被工作线程取出时只会执行 queued 状态的任务，暂停后将永远不会运行
+        with _queue_lock:
+            if workflow_sessions[session_id]["status"] == "queued":
+                return app_error(ErrorCode.INVALID_STATE, "工作流仍在排队，无法暂停", session_id)
+            workflow_sessions[session_id]["status"] = "paused"
         
         logger.info(f"工作流已暂停: {session_id}")
         
@@ -129,20 +268,17 @@ def api_pause_workflow(session_id: str) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"暂停工作流失败: {e}")
-        return {
-            "success": False,
-            "error": f"暂停工作流时发生错误: {str(e)}"
-        }
+        return error_response(e, f"暂停工作流时发生错误: {str(e)}", session_id=session_id)
 
 
 def api_resume_workflow(session_id: str) -> Dict[str, Any]:
     """恢复工作流"""
     try:
         if session_id not in workflow_sessions:
-            return {
-                "success": False,
-                "error": "工作流会话不存在"
-            }
+            return app_error(ErrorCode.NOT_FOUND, "工作流会话不存在", session_id)
+        
+        if workflow_sessions[session_id]["status"] == "queued":
+            return app_error(ErrorCode.INVALID_STATE, "工作流仍在排队", session_id)
         
         workflow_sessions[session_id]["status"] = "running"
         
@@ -155,22 +291,20 @@ def api_resume_workflow(session_id: str) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"恢复工作流失败: {e}")
-        return {
-            "success": False,
-            "error": f"恢复工作流时发生错误: {str(e)}"
-        }
+        return error_response(e, f"恢复工作流时发生错误: {str(e)}", session_id=session_id)
 
 
 def api_stop_workflow(session_id: str) -> Dict[str, Any]:
     """停止工作流"""
     try:
         if session_id not in workflow_sessions:
-            return {
-                "success": False,
-                "error": "工作流会话不存在"
-            }
+            return app_error(ErrorCode.NOT_FOUND, "工作流会话不存在", session_id)
         
-        workflow_sessions[session_id]["status"] = "stopped"
+        with _queue_lock:
+            workflow_sessions[session_id]["status"] = "stopped"
+            # 排队中的任务立即移出队列，后面任务的排队位置随之前移
+            if session_id in _workflow_queue:
+                _workflow_queue.remove(session_id)
         
         logger.info(f"工作流已停止: {session_id}")
         
@@ -181,11 +315,150 @@ def api_stop_workflow(session_id: str) -> Dict[str, Any]:
         
     except Exception as e:
         logger.error(f"停止工作流失败: {e}")
+        return error_response(e, f"停止工作流时发生错误: {str(e)}", session_id=session_id)
+
+
+def _trace_session_ids(session_id: str) -> List[str]:
+    """追踪包含的会话：澄清会话同时包含由它启动的工作流"""
+    session_ids = [session_id]
+    if is_valid_storage_id(session_id):
+        clarification = SessionStorage().load_session(session_id)
+        if clarification and clarification.get("workflow_session_id"):
+            session_ids.append(clarification["workflow_session_id"])
+    return session_ids
+
+
+def _trace_span(entry: Dict[str, Any]) -> Dict[str, Any]:
+    """审计记录 -> 时间线条目；记录写入时间即结束时间，未记录开始时间的按耗时倒推"""
+    ended_at = datetime.fromisoformat(entry["timestamp"])
+    duration_ms = entry.get("duration_ms")
+    started_at = entry.get("started_at") or (ended_at - timedelta(milliseconds=duration_ms or 0)).isoformat()
+    return {
+        "kind": entry["kind"],
+        "name": entry["action"],
+        "session_id": entry.get("session_id"),
+        "agent": entry.get("agent") or (entry["action"] if entry["kind"] == "agent" else None),
+        "provider": entry["action"] if entry["kind"] == "model_call" else entry.get("provider"),
+        "model": entry.get("model"),
+        "attempt": entry.get("attempt"),
+        "started_at": started_at,
+        "ended_at": entry["timestamp"],
+        "duration_ms": duration_ms,
+        "success": entry.get("success"),
+        "error_code": entry.get("error_code"),
+        "input_tokens": entry.get("input_tokens"),
+        "output_tokens": entry.get("output_tokens"),
+        "cached": bool(entry.get("cached")),
+        "fallback": bool(entry.get("fallback")),
+        "degradation": entry.get("degradation"),
+    }
+
+
+def api_get_session_trace(session_id: str) -> Dict[str, Any]:
+    """获取会话的执行时间线：接口调用、工作流阶段、智能体执行与每次模型请求（含重试、回退与 token 用量）
+
+    时间线来自审计日志（audit_log_enabled 关闭时不记录）；澄清会话同时包含由它启动的工作流。
+    """
+    try:
+        session_ids = _trace_session_ids(session_id)
+        entries = [
+            entry
+            for traced_id in session_ids
+            for entry in read_audit_log(session_id=traced_id, limit=0)
+            if entry.get("kind") in TRACE_KINDS
+        ]
+        if not entries and session_id not in workflow_sessions and len(session_ids) == 1:
+            if not (is_valid_storage_id(session_id) and SessionStorage().load_session(session_id)):
+                return app_error(ErrorCode.NOT_FOUND, "会话不存在", session_id)
+
+        spans = sorted((_trace_span(entry) for entry in entries), key=lambda span: span["started_at"])
+        calls = [span for span in spans if span["kind"] == "model_call"]
+        totals = {
+            "model_calls": len(calls),
+            "failed_calls": len([1 for span in calls if not span["success"]]),
+            "retries": len([1 for span in calls if span["attempt"]]),
+            "cached_calls": len([1 for span in calls if span["cached"]]),
+            "fallbacks": len([1 for span in spans if span["kind"] == "agent" and span["fallback"]]),
+            "input_tokens": sum(span["input_tokens"] or 0 for span in calls),
+            "output_tokens": sum(span["output_tokens"] or 0 for span in calls),
+            "model_time_ms": sum(span["duration_ms"] or 0 for span in calls),
+        }
+
+        return {
+            "success": True,
+            "data": {
+                "session_id": session_id,
+                "session_ids": session_ids,
+                "started_at": spans[0]["started_at"] if spans else None,
+                "ended_at": max(span["ended_at"] for span in spans) if spans else None,
+                "totals": totals,
+                "spans": spans
+            }
+        }
+
+    except Exception as e:
+        logger.error(f"获取会话时间线失败: {e}")
+        return error_response(e, f"获取会话时间线时发生错误: {str(e)}", session_id=session_id)
+
+
+def api_export_session_trace(session_id: str, output_path: Optional[str] = None) -> Dict[str, Any]:
+    """导出会话时间线为 Chrome trace_event 格式（可在 chrome://tracing 或 Perfetto 中打开）"""
+    try:
+        trace = api_get_session_trace(session_id)
+        if not trace["success"]:
+            return trace
+
+        trace_events = []
+        for span in trace["data"]["spans"]:
+            ts = int(datetime.fromisoformat(span["started_at"]).timestamp() * 1_000_000)
+            # 每类条目一条轨道；模型请求按智能体与提供商分轨
+            if span["kind"] == "model_call":
+                tid = f"{span['agent'] or 'model'}:{span['provider']}"
+            elif span["kind"] == "agent":
+                tid = span["agent"]
+            elif span["kind"] == "stage":
+                tid = "workflow"
+            else:
+                tid = "commands"
+            args = {
+                key: span[key]
+                for key in ("session_id", "provider", "model", "attempt", "success", "error_code",
+                            "input_tokens", "output_tokens", "cached", "fallback", "degradation")
+                if span[key] not in (None, False)
+            }
+            trace_events.append({
+                "name": span["name"], "cat": span["kind"], "ph": "X",
+                "ts": ts, "dur": (span["duration_ms"] or 0) * 1000,
+                "pid": 1, "tid": tid, "args": args
+            })
+
+        if output_path:
+            trace_file = Path(output_path)
+        else:
+            trace_file = app_config.data_dir / "traces" / f"{session_id}.trace.json"
+        trace_file.parent.mkdir(parents=True, exist_ok=True)
+
+        with open(trace_file, 'w', encoding='utf-8') as f:
+            json.dump({
+                "traceEvents": redact_data(trace_events, confidential_terms()),
+                "displayTimeUnit": "ms",
+                "otherData": {"session_id": session_id, **trace["data"]["totals"]}
+            }, f, ensure_ascii=False, indent=2)
+
+        logger.info(f"会话时间线已导出: {trace_file}")
+
         return {
-            "success": False,
-            "error": f"停止工作流时发生错误: {str(e)}"
+            "success": True,
+            "data": {
+                "path": str(trace_file),
+                "event_count": len(trace_events)
+            }
         }
 
+    except Exception as e:
+        logger.error(f"导出会话时间线失败: {e}")
+        return error_response(e, f"导出会话时间线时发生错误: {str(e)}", session_id=session_id)
+
 
 async def simulate_workflow_progress(session_id: str):
     """模拟工作流进度"""
@@ -202,9 +475,12 @@ async def simulate_workflow_progress(session_id: str):
             ("completed", "工作流已完成", 100)
         ]
         
-        for stage, message, progress in stages:
+        started_at = datetime.fromisoformat(session["created_at"])
+        
+        for index, (stage, message, progress) in enumerate(stages):
             if session["status"] != "running":
                 break
+            stage_started, stage_started_at = time.monotonic(), datetime.now().isoformat()
             
             # 更新会话状态
             session["stage"] = stage
@@ -214,17 +490,27 @@ async def simulate_workflow_progress(session_id: str):
                 "message": message,
                 "type": "system"
             })
-            
-            # 发布进度事件
-            event_bus.emit(EventTypes.WORKFLOW_PROGRESS_UPDATED, {
+            session["progress_detail"] = {
                 "session_id": session_id,
                 "stage": stage,
+                "stage_index": index + 1,
+                "stage_count": len(stages),
+                "agent": STAGE_AGENTS.get(stage),
                 "progress": progress,
-                "message": message
-            })
+                "message": message,
+                "elapsed_ms": int((datetime.now() - started_at).total_seconds() * 1000),
+                "updated_at": datetime.now().isoformat()
+            }
+            
+            # 发布进度事件
+            event_bus.emit(EventTypes.WORKFLOW_PROGRESS_UPDATED, session["progress_detail"])
             
             # 模拟处理时间
             await asyncio.sleep(3)
+            
+            # 阶段耗时写入审计日志，供会话时间线使用
+            record_audit("stage", stage, session_id=session_id, agent=STAGE_AGENTS.get(stage), started_at=stage_started_at,
+                         duration_ms=int((time.monotonic() - stage_started) * 1000), status=session["status"])
         
         # 完成工作流
         if session["status"] == "running":
@@ -0,0 +1,244 @@
+"""
+Audit Log
+按时间顺序记录接口调用、配置修改、密钥更新（遮盖）与智能体执行，写入数据目录下的 audit.jsonl（每行一个 JSON 对象），
+用于排查问题以及追溯一份报告是如何产生的；文件按 audit_max_size_mb 滚动，保留 audit_backup_count 份
+"""
+
+import asyncio
+import functools
+import inspect
+import json
+import logging
+import logging.handlers
+import threading
+import time
+from datetime import datetime
+from pathlib import Path
+from typing import Dict, List, Any, Callable, Optional
+
+from .logger import get_logger, get_log_context
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+# 记录类型
+AUDIT_KINDS = ("command", "config", "key", "agent", "model_call", "stage")
+
+# 参数中超过该长度的字符串会被截断
+MAX_VALUE_LENGTH = 200
+
+# 名称中含有这些词的字段按密钥处理，只记录遮盖后的值
+SECRET_MARKERS = ("api_key", "key", "token", "secret", "password")
+
+# 只读与轮询类接口（如前端定时调用的 api_get_workflow_status）不记录
+READ_ONLY_PREFIXES = ("api_get_", "api_list_", "api_search_", "api_load_")
+
+_handler: Optional[logging.handlers.RotatingFileHandler] = None
+_handler_key: Optional[tuple] = None
+_handler_lock = threading.Lock()
+
+
+def audit_path() -> Path:
+    return app_config.data_dir / "audit.jsonl"
+
+
+def is_read_only_command(name: str) -> bool:
+    return name.startswith(READ_ONLY_PREFIXES)
+
+
+def _audit_handler() -> logging.handlers.RotatingFileHandler:
+    """与应用日志相同的滚动文件写入；数据目录或滚动设置变化时重新创建"""
+    global _handler, _handler_key
+    path = audit_path()
+    key = (path, app_config.audit_max_size_mb, app_config.audit_backup_count)
+    with _handler_lock:
+        if _handler is None or _handler_key != key:
+            if _handler is not None:
+                _handler.close()
+            path.parent.mkdir(parents=True, exist_ok=True)
+            _handler = logging.handlers.RotatingFileHandler(
+                path,
+                maxBytes=int(app_config.audit_max_size_mb * 1024 * 1024),
+                backupCount=app_config.audit_backup_count,
+                encoding='utf-8',
+            )
+            _handler.setFormatter(logging.Formatter("%(message)s"))
+            _handler_key = key
+        return _handler
+
+
+def mask_secret(value: Any) -> Optional[str]:
+    """只保留前后各 4 个字符，例如 sk-p…wxyz；较短的值整体遮盖"""
+    if not value:
+        return None
+    text = str(value)
+    if len(text) <= 12:
+        return "****"
+    return f"{text[:4]}…{text[-4:]}"
+
+
+def _is_secret(name: str) -> bool:
+    name = name.lower()
+    return any(name == marker or name.endswith(f"_{marker}") for marker in SECRET_MARKERS)
+
+
+def summarize_value(value: Any, name: str = "") -> Any:
+    """用于记录的参数摘要：遮盖密钥字段与敏感内容，截断过长的字符串"""
+    from .safety import confidential_terms, redact_data
+
+    if name and _is_secret(name) and isinstance(value, str):
+        return mask_secret(value)
+    if isinstance(value, dict):
+        return {key: summarize_value(item, str(key)) for key, item in value.items()}
+    if isinstance(value, (list, tuple)):
+        return [summarize_value(item) for item in value]
+    if isinstance(value, str):
+        value = redact_data(value, confidential_terms())
+        if len(value) > MAX_VALUE_LENGTH:
+            return f"{value[:MAX_VALUE_LENGTH]}…（共 {len(value)} 字符）"
+        return value
+    if value is None or isinstance(value, (bool, int, float)):
+        return value
+    return summarize_value(str(value))
+
+
+def record_audit(
+    kind: str,
+    action: str,
+    success: bool = True,
+    session_id: Optional[str] = None,
+    **details: Any,
+) -> Optional[Dict[str, Any]]:
+    """追加一条记录；session_id 缺省时取 log_context 中的值。写入失败只记日志，不影响调用方"""
+    if not app_config.audit_log_enabled:
+        return None
+    entry = {
+        "timestamp": datetime.now().isoformat(),
+        "kind": kind,
+        "action": action,
+        "success": success,
+        "session_id": session_id or get_log_context().get("session_id"),
+        **details,
+    }
+    try:
+        line = json.dumps(entry, ensure_ascii=False, default=str)
+        # handle() 在处理器自身的锁内完成滚动与写入
+        _audit_handler().handle(logging.makeLogRecord({"msg": line, "levelno": logging.INFO, "levelname": "INFO"}))
+    except OSError as e:
+        logger.warning(f"写入审计日志失败: {e}")
+    return entry
+
+
+def _record_command(name: str, arguments: Dict[str, Any], result: Any, started: float, error: Optional[Exception]) -> None:
+    success = error is None and not (isinstance(result, dict) and result.get("success") is False)
+    error_code = None
+    if error is not None:
+        error_code = "internal"
+    elif isinstance(result, dict) and not success:
+        error_code = (result.get("error_info") or {}).get("code")
+    # 创建会话的接口没有 session_id 参数，取返回结果中的会话
+    session_id = arguments.get("session_id")
+    if not session_id and isinstance(result, dict):
+        data = result.get("data")
+        session_id = result.get("session_id") or (data.get("session_id") if isinstance(data, dict) else None)
+    record_audit(
+        "command",
+        name,
+        success=success,
+        session_id=session_id if isinstance(session_id, str) else None,
+        arguments=summarize_value(arguments),
+        duration_ms=int((time.monotonic() - started) * 1000),
+        error_code=error_code,
+    )
+
+
+def audited(func: Callable) -> Callable:
+    """包装接口函数，每次调用后记录一条 command 记录（名称、参数摘要、耗时与结果）；支持协程函数"""
+    signature = inspect.signature(func)
+
+    def _arguments(args, kwargs) -> Dict[str, Any]:
+        try:
+            return dict(signature.bind_partial(*args, **kwargs).arguments)
+        except TypeError:
+            return {"args": list(args), **kwargs}
+
+    if asyncio.iscoroutinefunction(func):
+        @functools.wraps(func)
+        async def async_wrapper(*args, **kwargs):
+            started = time.monotonic()
+            result, error = None, None
+            try:
+                result = await func(*args, **kwargs)
+                return result
+            except Exception as e:
+                error = e
+                raise
+            finally:
+                _record_command(func.__name__, _arguments(args, kwargs), result, started, error)
+        return async_wrapper
+
+    @functools.wraps(func)
+    def wrapper(*args, **kwargs):
+        started = time.monotonic()
+        result, error = None, None
+        try:
+            result = func(*args, **kwargs)
+            return result
+        except Exception as e:
+            error = e
+            raise
+        finally:
+            _record_command(func.__name__, _arguments(args, kwargs), result, started, error)
+    return wrapper
+
+
+def _audit_files() -> List[Path]:
+    """当前文件与滚动备份，由新到旧"""
+    path = audit_path()
+    files = [path] + [path.with_name(f"{path.name}.{index}") for index in range(1, app_config.audit_backup_count + 1)]
+    return [file for file in files if file.exists()]
+
+
+def read_audit_log(
+    kind: Optional[str] = None,
+    action: Optional[str] = None,
+    session_id: Optional[str] = None,
+    since: Optional[str] = None,
+    until: Optional[str] = None,
+    success: Optional[bool] = None,
+    limit: int = 200,
+) -> List[Dict[str, Any]]:
+    """按条件筛选记录，最新的在前；since / until 为 ISO 时间或日期字符串（until 为日期时包含当天）
+
+    读取时不持有写入锁：按文件由新到旧逐行倒序筛选，凑够 limit 条即停止；正在写入的不完整行会被跳过。
+    """
+    entries: List[Dict[str, Any]] = []
+    for file in _audit_files():
+        try:
+            with open(file, 'r', encoding='utf-8') as f:
+                lines = f.readlines()
+        except OSError as e:
+            logger.warning(f"读取审计日志失败: {e}")
+            continue
+        for line in reversed(lines):
+            try:
+                entry = json.loads(line)
+            except json.JSONDecodeError:
+                continue
+            timestamp = entry.get("timestamp", "")
+            if since and timestamp < since:
+                continue
+            if kind and entry.get("kind") != kind:
+                continue
+            if action and entry.get("action") != action:
+                continue
+            if session_id and entry.get("session_id") != session_id:
+                continue
+            if until and timestamp[:len(until)] > until:
+                continue
+            if success is not None and entry.get("success") != success:
+                continue
+            entries.append(entry)
+            if limit and limit > 0 and len(entries) >= limit:
+                return entries
+    return entries
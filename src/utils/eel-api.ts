@@ -17,9 +17,13 @@ declare global {
       // 工作流API
       api_start_workflow: (project_id: string, initial_idea: string, workflow_mode?: string) => Promise<any>;
       api_get_workflow_status: (session_id: string) => Promise<any>;
+      api_get_session_progress: (session_id: string) => Promise<any>;
+      api_list_running_jobs: () => Promise<any>;
       api_pause_workflow: (session_id: string) => Promise<any>;
       api_resume_workflow: (session_id: string) => Promise<any>;
       api_stop_workflow: (session_id: string) => Promise<any>;
+      api_get_session_trace: (session_id: string) => Promise<any>;
+      api_export_session_trace: (session_id: string, output_path?: string) => Promise<any>;
       
       // 智能体API
       api_list_agents: () => Promise<any>;
@@ -30,15 +34,52 @@ declare global {
       // 模型API
       api_call_ai_model: (provider: string, model: string, messages: any[], config?: any) => Promise<any>;
       api_test_model_connection: (provider: string, model: string) => Promise<any>;
-      api_list_available_models: () => Promise<any>;
+      api_test_ai_connection: () => Promise<any>;
+      api_validate_api_key: (provider: string, key: string) => Promise<any>;
+      api_list_available_models: (provider?: string, refresh?: boolean) => Promise<any>;
       api_get_model_config: (provider: string, model: string) => Promise<any>;
 
   // 澄清会话API
-  api_start_clarification_session: (seed: any) => Promise<any>;
+  api_start_clarification_session: (seed: any, model_overrides?: any) => Promise<any>;
   api_submit_clarification_answer: (session_id: string, slot_name: string, answer: string) => Promise<any>;
   api_get_clarification_status: (session_id: string) => Promise<any>;
   api_finish_clarification: (session_id: string) => Promise<any>;
   api_submit_summary: (session_id: string, summary: any, restart?: boolean) => Promise<any>;
+  api_list_sessions: (tag?: string, folder?: string) => Promise<any>;
+  api_search_sessions: (query: string, limit?: number) => Promise<any>;
+  api_set_session_tags: (session_id: string, tags: string[]) => Promise<any>;
+  api_set_session_folder: (session_id: string, folder?: string) => Promise<any>;
+  api_list_tags: () => Promise<any>;
+  api_delete_session: (session_id: string) => Promise<any>;
+  api_rename_session: (session_id: string, title: string) => Promise<any>;
+  api_duplicate_session: (session_id: string) => Promise<any>;
+  api_set_confidential_terms: (session_id: string, terms: string[]) => Promise<any>;
+  api_get_confidential_terms: (session_id: string) => Promise<any>;
+  api_recover_session: (session_id: string) => Promise<any>;
+  api_list_domain_templates: (language?: string) => Promise<any>;
+  api_list_prompts: () => Promise<any>;
+  api_reload_prompts: () => Promise<any>;
+  api_get_summary: (session_id: string, version?: number) => Promise<any>;
+  api_regenerate_summary: (session_id: string) => Promise<any>;
+  api_update_summary: (session_id: string, changes: any) => Promise<any>;
+  api_rollback_summary: (session_id: string, version: number) => Promise<any>;
+  api_get_analytics: (weeks?: number) => Promise<any>;
+      
+      // 导出API
+      api_export_result: (session_id: string, format?: string, output_path?: string) => Promise<any>;
+      api_export_session_bundle: (session_id: string, output_path?: string, include_sensitive?: boolean) => Promise<any>;
+      api_import_session_bundle: (path: string) => Promise<any>;
+      
+      // 系统API
+      api_get_storage_paths: () => Promise<any>;
+      api_open_data_directory: () => Promise<any>;
+      api_save_config: (settings: any) => Promise<any>;
+      api_get_config: () => Promise<any>;
+      api_get_cache_stats: () => Promise<any>;
+      api_get_rate_limit_stats: () => Promise<any>;
+      api_get_system_health: (refresh?: boolean) => Promise<any>;
+      api_get_audit_log: (kind?: string, action?: string, sessionId?: string, since?: string, until?: string, success?: boolean, limit?: number) => Promise<any>;
+      api_clear_cache: () => Promise<any>;
       
       // 应用信息API
       get_app_info: () => Promise<any>;
@@ -56,6 +97,17 @@ declare global {
   }
 }
 
+/**
+ * 失败响应中的结构化错误（{ success: false, error, error_info }），code 见 src/utils/errors.py 的 ErrorCode
+ */
+export interface ApiErrorInfo {
+  code: string;
+  message: string;
+  retryable: boolean;
+  provider?: string | null;
+  session_id?: string | null;
+}
+
 /**
  * Eel API包装器，提供与Tauri API兼容的接口
  */
@@ -103,7 +155,7 @@ export class EelAPI {
       case 'run_clarification_ai':
         return await EelAPI.call('run_clarification_ai', args.ideaContent ?? args.text ?? '');
       case 'start_clarification_session':
-        return await EelAPI.call('api_start_clarification_session', args.seed ?? { raw_text: args.text ?? '' });
+        return await EelAPI.call('api_start_clarification_session', args.seed ?? { raw_text: args.text ?? '' }, args.model_overrides);
       case 'submit_clarification_answer':
         return await EelAPI.call('api_submit_clarification_answer', args.session_id, args.slot_name, args.answer);
       case 'get_clarification_status':
@@ -112,6 +164,44 @@ export class EelAPI {
         return await EelAPI.call('api_finish_clarification', args.session_id);
       case 'submit_summary':
         return await EelAPI.call('api_submit_summary', args.session_id, args.summary, args.restart ?? true);
+      case 'list_sessions':
+        return await EelAPI.call('api_list_sessions', args?.tag, args?.folder);
+      case 'search_sessions':
+        return await EelAPI.call('api_search_sessions', args.query, args.limit);
+      case 'set_session_tags':
+        return await EelAPI.call('api_set_session_tags', args.session_id, args.tags);
+      case 'set_session_folder':
+        return await EelAPI.call('api_set_session_folder', args.session_id, args.folder);
+      case 'list_tags':
+        return await EelAPI.call('api_list_tags');
+      case 'delete_session':
+        return await EelAPI.call('api_delete_session', args.session_id);
+      case 'rename_session':
+        return await EelAPI.call('api_rename_session', args.session_id, args.title);
+      case 'duplicate_session':
+        return await EelAPI.call('api_duplicate_session', args.session_id);
+      case 'set_confidential_terms':
+        return await EelAPI.call('api_set_confidential_terms', args.session_id, args.terms ?? []);
+      case 'get_confidential_terms':
+        return await EelAPI.call('api_get_confidential_terms', args.session_id);
+      case 'recover_session':
+        return await EelAPI.call('api_recover_session', args.session_id);
+      case 'list_domain_templates':
+        return await EelAPI.call('api_list_domain_templates', args?.language);
+      case 'list_prompts':
+        return await EelAPI.call('api_list_prompts');
+      case 'reload_prompts':
+        return await EelAPI.call('api_reload_prompts');
+      case 'get_summary':
+        return await EelAPI.call('api_get_summary', args.session_id, args.version);
+      case 'regenerate_summary':
+        return await EelAPI.call('api_regenerate_summary', args.session_id);
+      case 'update_summary':
+        return await EelAPI.call('api_update_summary', args.session_id, args.changes);
+      case 'rollback_summary':
+        return await EelAPI.call('api_rollback_summary', args.session_id, args.version);
+      case 'get_analytics':
+        return await EelAPI.call('api_get_analytics', args?.weeks);
       // 项目管理命令
       case 'create_project':
         // Python 端期望一个 dict 参数，这里按对象传递，避免参数数量不匹配
@@ -166,6 +256,12 @@ export class EelAPI {
       case 'get_workflow_status':
         return await EelAPI.call('api_get_workflow_status', args.session_id);
 
+      case 'get_session_progress':
+        return await EelAPI.call('api_get_session_progress', args.session_id);
+
+      case 'list_running_jobs':
+        return await EelAPI.call('api_list_running_jobs');
+
       case 'pause_workflow':
         return await EelAPI.call('api_pause_workflow', args.session_id);
 
@@ -175,6 +271,12 @@ export class EelAPI {
       case 'stop_workflow':
         return await EelAPI.call('api_stop_workflow', args.session_id);
 
+      case 'get_session_trace':
+        return await EelAPI.call('api_get_session_trace', args.session_id);
+
+      case 'export_session_trace':
+        return await EelAPI.call('api_export_session_trace', args.session_id, args.output_path);
+
       // 兼容工作区页面的按键命令（映射到工作流控制）
       case 'start_agent_workflow':
         // 将“开始”映射为恢复运行
@@ -204,8 +306,47 @@ export class EelAPI {
       case 'test_model_connection':
         return await EelAPI.call('api_test_model_connection', args.provider, args.model);
 
+      case 'test_ai_connection':
+        return await EelAPI.call('api_test_ai_connection');
+
+      case 'validate_api_key':
+        return await EelAPI.call('api_validate_api_key', args.provider, args.key);
+
       case 'list_available_models':
-        return await EelAPI.call('api_list_available_models');
+        return await EelAPI.call('api_list_available_models', args?.provider, args?.refresh);
+
+      // 导出命令
+      case 'export_result':
+        return await EelAPI.call('api_export_result', args.session_id, args.format, args.output_path);
+
+      case 'export_session_bundle':
+        return await EelAPI.call('api_export_session_bundle', args.session_id, args.output_path, args.include_sensitive);
+
+      case 'import_session_bundle':
+        return await EelAPI.call('api_import_session_bundle', args.path);
+
+      // 系统命令
+      case 'get_storage_paths':
+        return await EelAPI.call('api_get_storage_paths');
+
+      case 'open_data_directory':
+        return await EelAPI.call('api_open_data_directory');
+
+      case 'save_config':
+        return await EelAPI.call('api_save_config', args.settings);
+
+      case 'get_config':
+        return await EelAPI.call('api_get_config');
+      case 'get_cache_stats':
+        return await EelAPI.call('api_get_cache_stats');
+      case 'get_rate_limit_stats':
+        return await EelAPI.call('api_get_rate_limit_stats');
+      case 'get_system_health':
+        return await EelAPI.call('api_get_system_health', args?.refresh);
+      case 'get_audit_log':
+        return await EelAPI.call('api_get_audit_log', args?.kind, args?.action, args?.sessionId ?? args?.session_id, args?.since, args?.until, args?.success, args?.limit);
+      case 'clear_cache':
+        return await EelAPI.call('api_clear_cache');
 
       // 应用信息命令
       case 'get_app_info':
@@ -0,0 +1,102 @@
+"""
+API Error Utilities
+接口统一的失败响应：保留原有的 error 文本，并附加结构化的 error_info（code、message、retryable、provider、session_id），
+供前端按错误码本地化提示并决定是否提供重试
+"""
+
+from dataclasses import dataclass, asdict
+from enum import Enum
+from typing import Dict, Any, Optional
+
+
+class ErrorCode(str, Enum):
+    NOT_FOUND = "not_found"
+    INVALID_INPUT = "invalid_input"
+    UNSUPPORTED = "unsupported"
+    INVALID_STATE = "invalid_state"
+    NOT_CONFIGURED = "not_configured"
+    STORAGE = "storage_error"
+    PROVIDER = "provider_error"
+    # 与 ModelErrorKind 一一对应
+    RATE_LIMITED = "rate_limited"
+    TIMEOUT = "timeout"
+    INVALID_KEY = "invalid_key"
+    CONTEXT_TOO_LONG = "context_too_long"
+    UNAVAILABLE = "unavailable"
+    BAD_REQUEST = "bad_request"
+    BAD_RESPONSE = "bad_response"
+    AGENT_UNAVAILABLE = "agent_unavailable"
+    INTERNAL = "internal"
+
+
+# 默认可重试的错误码
+RETRYABLE_CODES = {
+    ErrorCode.PROVIDER, ErrorCode.RATE_LIMITED, ErrorCode.TIMEOUT, ErrorCode.UNAVAILABLE, ErrorCode.AGENT_UNAVAILABLE,
+}
+
+
+@dataclass
+class AppError(Exception):
+    code: ErrorCode
+    message: str
+    retryable: Optional[bool] = None
+    provider: Optional[str] = None
+    session_id: Optional[str] = None
+
+    def __post_init__(self):
+        super().__init__(self.message)
+        if self.retryable is None:
+            self.retryable = self.code in RETRYABLE_CODES
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data["code"] = self.code.value
+        return data
+
+    def to_response(self) -> Dict[str, Any]:
+        return {"success": False, "error": self.message, "error_info": self.to_dict()}
+
+
+def app_error(code: ErrorCode, message: str, session_id: Optional[str] = None, **fields: Any) -> Dict[str, Any]:
+    """预期内失败（不存在、参数错误等）的响应"""
+    return AppError(code, message, session_id=session_id, **fields).to_response()
+
+
+def classify_exception(error: Exception) -> AppError:
+    """把异常归类为 AppError；模型调用错误保留提供商与可重试信息"""
+    from .providers import ModelError
+    from .intake import IntakeError
+
+    if isinstance(error, AppError):
+        return error
+    if isinstance(error, ModelError):
+        return AppError(ErrorCode(error.kind.value), str(error), error.retryable, error.provider)
+    if isinstance(error, IntakeError):
+        return AppError(ErrorCode.INVALID_INPUT, str(error))
+    if isinstance(error, ValueError):
+        return AppError(ErrorCode.INVALID_INPUT, str(error))
+    # TimeoutError 是 OSError 的子类，需先于存储错误判断
+    if isinstance(error, TimeoutError):
+        return AppError(ErrorCode.TIMEOUT, str(error))
+    if isinstance(error, OSError):
+        return AppError(ErrorCode.STORAGE, str(error))
+    return AppError(ErrorCode.INTERNAL, str(error))
+
+
+def error_response(error: Exception, message: Optional[str] = None, session_id: Optional[str] = None) -> Dict[str, Any]:
+    """异常的失败响应；message 覆盖展示给用户的 error 文本"""
+    app = classify_exception(error)
+    if message is not None:
+        app = AppError(app.code, message, app.retryable, app.provider, app.session_id)
+    if session_id is not None:
+        app.session_id = session_id
+    return app.to_response()
+
+
+def forward_error(result: Dict[str, Any], fallback: str, session_id: Optional[str] = None) -> Dict[str, Any]:
+    """转发下游接口的失败响应，保留其 error_info"""
+    info = dict(result.get("error_info") or {"code": ErrorCode.INTERNAL.value, "message": result.get("error") or fallback,
+                                              "retryable": False, "provider": None})
+    if session_id is not None:
+        info["session_id"] = session_id
+    return {"success": False, "error": result.get("error") or fallback, "error_info": info}
@@ -68,6 +68,8 @@ class EventTypes:
     AGENT_STATUS_CHANGED = "agent.status_changed"
     AGENT_MESSAGE_SENT = "agent.message_sent"
     AGENT_MESSAGE_RECEIVED = "agent.message_received"
+    # 流式输出的文本片段（enable_streaming 开启时），最后一条 done 为真
+    AGENT_OUTPUT_CHUNK = "agent.output_chunk"
     
     # 讨论事件
     DISCUSSION_STARTED = "discussion.started"
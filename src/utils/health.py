@@ -0,0 +1,162 @@
+"""
+Health Check Utilities
+后台定期检查已配置的模型提供商与存储目录，供界面显示状态指示并在不可用时停用相关功能
+"""
+
+import tempfile
+import threading
+import time
+from concurrent.futures import ThreadPoolExecutor
+from datetime import datetime
+from pathlib import Path
+from typing import Dict, List, Any, Optional
+
+from .logger import get_logger
+from .event_bus import event_bus, EventTypes
+from .providers import configured_providers, probe_provider
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+_last_health: Optional[Dict[str, Any]] = None
+_health_lock = threading.Lock()
+_health_thread: Optional[threading.Thread] = None
+
+
+def check_providers(timeout: float = 10) -> List[Dict[str, Any]]:
+    """并行请求每个已配置提供商的模型列表接口（不产生计费请求）；离线模式下不检查"""
+    if app_config.offline:
+        return []
+    providers = configured_providers()
+    if not providers:
+        return []
+    with ThreadPoolExecutor(max_workers=len(providers)) as pool:
+        return list(pool.map(lambda provider: probe_provider(provider, timeout=timeout), providers))
+
+
+def check_storage() -> List[Dict[str, Any]]:
+    """检查数据、项目、缓存与日志目录是否可写"""
+    results = []
+    for name, path in (
+        ("sessions", app_config.data_dir / "sessions"),
+        ("projects", app_config.projects_dir),
+        ("cache", app_config.cache_dir),
+        ("logs", app_config.logs_dir),
+    ):
+        started = time.monotonic()
+        try:
+            Path(path).mkdir(parents=True, exist_ok=True)
+            with tempfile.TemporaryFile(dir=path):
+                pass
+            error = None
+        except OSError as e:
+            error = str(e)
+        results.append({
+            "name": name,
+            "path": str(path),
+            "success": error is None,
+            "latency_ms": int((time.monotonic() - started) * 1000),
+            "error": error,
+        })
+    return results
+
+
+def run_health_check(include_providers: bool = True) -> Dict[str, Any]:
+    """执行一次检查并保存结果；整体状态变化时发布系统事件
+
+    include_providers 为假时不请求提供商，沿用上一次的提供商结果（从未检查过时按是否已配置密钥判断）。
+    status: ok | degraded（有提供商不可用或未配置任何提供商）| down（存储不可写）
+    """
+    global _last_health
+    with _health_lock:
+        previous_health = _last_health
+    if include_providers:
+        providers = check_providers()
+        providers_checked_at = datetime.now().isoformat()
+    elif previous_health and previous_health.get("providers_checked_at"):
+        providers = previous_health["providers"]
+        providers_checked_at = previous_health["providers_checked_at"]
+    else:
+        providers, providers_checked_at = None, None
+    storage = check_storage()
+
+    storage_ok = all(item["success"] for item in storage)
+    if providers is None:
+        providers = []
+        providers_ok = ai_available = not app_config.offline and bool(configured_providers())
+    else:
+        providers_ok = bool(providers) and all(item["success"] for item in providers)
+        ai_available = any(item["success"] for item in providers)
+    if not storage_ok:
+        status = "down"
+    elif app_config.offline or providers_ok:
+        status = "ok"
+    else:
+        status = "degraded"
+
+    health = {
+        "status": status,
+        "offline": app_config.offline,
+        "checked_at": datetime.now().isoformat(),
+        "providers_checked_at": providers_checked_at,
+        "providers": providers,
+        "storage": storage,
+        # 界面据此停用依赖模型或存储的功能；离线模式下澄清与总结使用内置规则，仍可使用
+        "features": {
+            "ai_models": ai_available,
+            "clarification": storage_ok,
+            "export": storage_ok,
+        },
+    }
+
+    with _health_lock:
+        previous = _last_health["status"] if _last_health else None
+        _last_health = health
+    if previous and previous != status:
+        event_type = EventTypes.SYSTEM_INFO if status == "ok" else EventTypes.SYSTEM_WARNING
+        event_bus.emit(event_type, {"health_status": status, "previous_status": previous})
+    if status != "ok":
+        failed = [item.get("provider") or item.get("name") for item in [*providers, *storage] if not item["success"]]
+        logger.warning(f"健康检查: {status}，异常项: {', '.join(failed) or '未配置模型提供商'}")
+    return health
+
+
+def get_system_health(refresh: bool = False) -> Dict[str, Any]:
+    """最近一次检查结果；尚未检查过或 refresh 为真时立即检查"""
+    with _health_lock:
+        health = _last_health
+    if health is None or refresh:
+        health = run_health_check()
+    return health
+
+
+def start_health_checker(interval_seconds: Optional[float] = None) -> None:
+    """启动后台线程，按 health_check_interval 定期检查存储目录；间隔为 0 时不启动
+
+    提供商只在 provider_check_interval 大于 0 时定期检查（默认关闭），实际间隔不小于 health_check_interval。
+    """
+    global _health_thread
+    if _health_thread and _health_thread.is_alive():
+        return
+    interval = interval_seconds if interval_seconds is not None else app_config.health_check_interval
+    if interval <= 0:
+        return
+
+    def _run():
+        last_provider_check: Optional[float] = None
+        while True:
+            provider_interval = app_config.provider_check_interval
+            now = time.monotonic()
+            check_providers_now = provider_interval > 0 and (
+                last_provider_check is None or now - last_provider_check >= provider_interval
+            )
+            try:
+                run_health_check(include_providers=check_providers_now)
+                if check_providers_now:
+                    last_provider_check = now
+            except Exception as e:
+                logger.error(f"健康检查失败: {e}")
+            time.sleep(interval)
+
+    _health_thread = threading.Thread(target=_run, name="health-checker", daemon=True)
+    _health_thread.start()
@@ -0,0 +1,66 @@
+"""
+Output Language Utilities
+智能体输出与报告使用的语言（与界面语言无关），以及 config/locales/<语言>.json 中的本地化文案
+"""
+
+import json
+from pathlib import Path
+from typing import Dict, Any, Optional
+
+from .logger import get_logger
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+LOCALES_DIR = Path(__file__).resolve().parent.parent.parent / "config" / "locales"
+
+DEFAULT_LANGUAGE = "zh-CN"
+
+# 支持的输出语言及其在提示词中的英文名称
+SUPPORTED_LANGUAGES: Dict[str, str] = {
+    "zh-CN": "Simplified Chinese",
+    "en-US": "English",
+    "ja-JP": "Japanese",
+}
+
+_catalogs: Dict[str, Dict[str, str]] = {}
+
+
+def normalize_language(language: Optional[str]) -> Optional[str]:
+    """把 en、EN_us、ja 等写法规范为受支持的语言代码；无法识别时返回 None"""
+    if not language:
+        return None
+    code = language.strip().replace("_", "-").lower()
+    for supported in SUPPORTED_LANGUAGES:
+        if code == supported.lower() or code == supported.split("-")[0].lower():
+            return supported
+    return None
+
+
+def output_language(language: Optional[str] = None) -> Optional[str]:
+    """显式指定的语言优先，其次是 output_language 设置；都未设置时返回 None（跟随想法原文的语言）"""
+    return normalize_language(language) or normalize_language(app_config.output_language)
+
+
+def language_instruction(language: Optional[str]) -> str:
+    """提示词中的输出语言要求（用于 ${language_instruction}）；未指定语言时为空"""
+    if not language:
+        return ""
+    return f" Write all natural-language text in {SUPPORTED_LANGUAGES[language]}, keeping JSON keys and slot_name values in English."
+
+
+def get_catalog(language: Optional[str]) -> Dict[str, str]:
+    language = normalize_language(language) or DEFAULT_LANGUAGE
+    if language not in _catalogs:
+        try:
+            _catalogs[language] = json.loads((LOCALES_DIR / f"{language}.json").read_text(encoding="utf-8"))
+        except Exception as e:
+            logger.warning(f"读取语言文件失败 {language}: {e}")
+            _catalogs[language] = {}
+    return _catalogs[language]
+
+
+def t(key: str, language: Optional[str] = None, **variables: Any) -> str:
+    """取本地化文案；缺失时回退到默认语言，再回退到键名本身"""
+    text = get_catalog(language).get(key) or get_catalog(DEFAULT_LANGUAGE).get(key) or key
+    return text.format(**variables) if variables else text
@@ -0,0 +1,208 @@
+"""
+Idea Intake Utilities
+把网页 URL 或本地文档（txt / md / html / pdf / docx）转换为想法原文与上下文提示
+"""
+
+import re
+import shutil
+import subprocess
+import zipfile
+from html.parser import HTMLParser
+from pathlib import Path
+from typing import Dict, List, Any, Optional
+from xml.etree import ElementTree
+
+import requests
+
+from .logger import get_logger
+
+logger = get_logger(__name__)
+
+# 想法原文的最大长度，超出部分截断（避免把整篇长文送入澄清提示词）
+INTAKE_MAX_CHARS = 20000
+
+TEXT_SUFFIXES = {".txt", ".md", ".markdown"}
+HTML_SUFFIXES = {".html", ".htm"}
+
+# 正文提取时整体跳过的标签
+SKIP_TAGS = {"script", "style", "noscript", "template", "svg", "nav", "header", "footer", "aside", "form"}
+BLOCK_TAGS = {"p", "div", "section", "article", "main", "li", "br", "h1", "h2", "h3", "h4", "h5", "h6", "tr", "blockquote", "pre"}
+
+DOCX_NS = "{http://schemas.openxmlformats.org/wordprocessingml/2006/main}"
+
+USER_AGENT = "Mozilla/5.0 (compatible; IdeaArchitect/1.0)"
+
+
+class IntakeError(Exception):
+    """无法读取或解析想法来源"""
+
+
+class _ReadableTextParser(HTMLParser):
+    """简单的正文提取：收集标题、描述与可见文本，存在 <article>/<main> 时只保留其中内容"""
+
+    def __init__(self):
+        super().__init__(convert_charrefs=True)
+        self.title = ""
+        self.description = ""
+        self._in_title = False
+        self._skip_depth = 0
+        self._main_depth = 0
+        self._all: List[str] = []
+        self._main: List[str] = []
+
+    def handle_starttag(self, tag, attrs):
+        if tag in SKIP_TAGS:
+            self._skip_depth += 1
+        elif tag in ("article", "main"):
+            self._main_depth += 1
+        elif tag == "title":
+            self._in_title = True
+        elif tag == "meta":
+            attrs = dict(attrs)
+            if (attrs.get("name") or attrs.get("property") or "").lower() in ("description", "og:description"):
+                self.description = self.description or (attrs.get("content") or "").strip()
+        if tag in BLOCK_TAGS:
+            self._append("\n")
+
+    def handle_endtag(self, tag):
+        if tag in SKIP_TAGS and self._skip_depth:
+            self._skip_depth -= 1
+        elif tag in ("article", "main") and self._main_depth:
+            self._main_depth -= 1
+        elif tag == "title":
+            self._in_title = False
+        if tag in BLOCK_TAGS:
+            self._append("\n")
+
+    def handle_data(self, data):
+        if self._in_title:
+            self.title += data
+        elif not self._skip_depth:
+            self._append(data)
+
+    def _append(self, text: str) -> None:
+        self._all.append(text)
+        if self._main_depth:
+            self._main.append(text)
+
+    @property
+    def text(self) -> str:
+        return _clean_text("".join(self._main) or "".join(self._all))
+
+
+def _clean_text(text: str) -> str:
+    lines = [re.sub(r"[ \t\u00a0]+", " ", line).strip() for line in text.splitlines()]
+    return re.sub(r"\n{3,}", "\n\n", "\n".join(lines)).strip()
+
+
+def is_url(source: str) -> bool:
+    return bool(re.match(r"^https?://\S+$", (source or "").strip(), re.IGNORECASE))
+
+
+def extract_html(markup: str) -> Dict[str, str]:
+    parser = _ReadableTextParser()
+    parser.feed(markup)
+    parser.close()
+    return {"title": _clean_text(parser.title), "description": parser.description, "text": parser.text}
+
+
+def extract_docx(path: Path) -> str:
+    """读取 docx 正文段落（word/document.xml），无需额外依赖"""
+    try:
+        with zipfile.ZipFile(path) as archive:
+            root = ElementTree.fromstring(archive.read("word/document.xml"))
+    except (zipfile.BadZipFile, KeyError, ElementTree.ParseError) as e:
+        raise IntakeError(f"无法解析 docx 文件: {e}")
+    paragraphs = ["".join(node.text or "" for node in para.iter(f"{DOCX_NS}t")) for para in root.iter(f"{DOCX_NS}p")]
+    return _clean_text("\n".join(paragraphs))
+
+
+def extract_pdf(path: Path, timeout: int = 60) -> str:
+    """读取 PDF 文本：优先使用已安装的 pypdf，其次使用系统的 pdftotext（poppler）"""
+    try:
+        from pypdf import PdfReader
+    except ImportError:
+        PdfReader = None
+    if PdfReader is not None:
+        return _clean_text("\n".join(page.extract_text() or "" for page in PdfReader(str(path)).pages))
+
+    pdftotext = shutil.which("pdftotext")
+    if not pdftotext:
+        raise IntakeError("读取 PDF 需要安装 pypdf 或 poppler（pdftotext）")
+    result = subprocess.run(
+        [pdftotext, "-layout", "-enc", "UTF-8", str(path), "-"],
+        capture_output=True,
+        timeout=timeout,
+    )
+    if result.returncode != 0:
+        raise IntakeError(f"pdftotext 失败: {result.stderr.decode('utf-8', 'replace').strip()}")
+    return _clean_text(result.stdout.decode("utf-8", "replace"))
+
+
+def fetch_url(url: str, timeout: int = 30) -> Dict[str, str]:
+    try:
+        resp = requests.get(url, timeout=timeout, headers={"User-Agent": USER_AGENT})
+    except requests.RequestException as e:
+        raise IntakeError(f"无法获取网页: {e}")
+    if resp.status_code >= 400:
+        raise IntakeError(f"无法获取网页: HTTP {resp.status_code}")
+    content_type = resp.headers.get("Content-Type", "")
+    if "html" in content_type or not content_type:
+        return extract_html(resp.text)
+    if content_type.startswith("text/"):
+        return {"title": "", "description": "", "text": _clean_text(resp.text)}
+    raise IntakeError(f"不支持的网页内容类型: {content_type}")
+
+
+def read_document(path: Path) -> Dict[str, str]:
+    if not path.is_file():
+        raise IntakeError(f"文件不存在: {path}")
+    suffix = path.suffix.lower()
+    if suffix in HTML_SUFFIXES:
+        return extract_html(path.read_text(encoding="utf-8", errors="replace"))
+    if suffix == ".pdf":
+        return {"title": path.stem, "description": "", "text": extract_pdf(path)}
+    if suffix == ".docx":
+        return {"title": path.stem, "description": "", "text": extract_docx(path)}
+    if suffix in TEXT_SUFFIXES or not suffix:
+        return {"title": "", "description": "", "text": _clean_text(path.read_text(encoding="utf-8", errors="replace"))}
+    raise IntakeError(f"不支持的文件类型: {suffix}")
+
+
+def load_idea_source(source: str, timeout: int = 30) -> Dict[str, Any]:
+    """读取 URL 或文件路径，返回 { raw_text, context_hints }；来源、标题与摘要作为上下文提示"""
+    source = source.strip()
+    if is_url(source):
+        extracted = fetch_url(source, timeout)
+    else:
+        extracted = read_document(Path(source).expanduser())
+
+    text = extracted["text"]
+    if not text:
+        raise IntakeError(f"未能从来源中提取到文本: {source}")
+    if len(text) > INTAKE_MAX_CHARS:
+        logger.info(f"想法来源文本过长，截断到 {INTAKE_MAX_CHARS} 字符: {source}")
+        text = text[:INTAKE_MAX_CHARS]
+
+    hints: List[str] = [f"来源: {source}"]
+    for key, label in (("title", "标题"), ("description", "摘要")):
+        if extracted.get(key):
+            hints.append(f"{label}: {extracted[key]}")
+    return {"raw_text": text, "context_hints": hints}
+
+
+def resolve_idea_seed(seed: Optional[Dict[str, Any]], timeout: int = 30) -> Dict[str, Any]:
+    """若 seed 带有 source（或 raw_text 只是一个 URL），抓取并解析后填充 raw_text 与 context_hints"""
+    seed = dict(seed or {})
+    source = seed.get("source")
+    if not source and is_url(seed.get("raw_text") or ""):
+        source = seed["raw_text"].strip()
+    if not source:
+        return seed
+
+    loaded = load_idea_source(source, timeout)
+    note = (seed.get("raw_text") or "").strip()
+    seed["source"] = source
+    seed["raw_text"] = loaded["raw_text"] if not note or note == source else f"{note}\n\n{loaded['raw_text']}"
+    seed["context_hints"] = [*(seed.get("context_hints") or []), *loaded["context_hints"]]
+    return seed
@@ -2,12 +2,117 @@
 Logging Utilities
 """
 
+import contextvars
+import hashlib
+import json
 import logging
+import logging.handlers
 import sys
+from contextlib import contextmanager
 from pathlib import Path
-from typing import Optional
+from typing import Optional, Dict, Any, Iterator
 from datetime import datetime
 
+from config.app_config import app_config
+
+
+# 当前调用链上的会话与智能体，写入每条日志
+_log_context: contextvars.ContextVar[Dict[str, Any]] = contextvars.ContextVar("log_context", default={})
+
+_handlers_installed = False
+
+
+@contextmanager
+def log_context(**fields: Any) -> Iterator[None]:
+    """在上下文内为日志附加字段，例如 log_context(session_id=..., agent="clarifier")"""
+    merged = {**_log_context.get(), **{k: v for k, v in fields.items() if v is not None}}
+    token = _log_context.set(merged)
+    try:
+        yield
+    finally:
+        _log_context.reset(token)
+
+
+def get_log_context() -> Dict[str, Any]:
+    """当前 log_context 中的字段"""
+    return dict(_log_context.get())
+
+
+class ContextFilter(logging.Filter):
+    """把 log_context 中的字段挂到日志记录上，并遮盖消息中的敏感内容"""
+
+    def filter(self, record: logging.LogRecord) -> bool:
+        context = _log_context.get()
+        record.context = context
+        record.context_text = "".join(f" [{key}={value}]" for key, value in context.items())
+        if app_config.redact_sensitive_data:
+            from .safety import confidential_terms, redact_text
+            message = record.getMessage()
+            redacted = redact_text(message, confidential_terms())
+            if redacted != message:
+                record.msg, record.args = redacted, ()
+        return True
+
+
+class JsonFormatter(logging.Formatter):
+    """每行一个 JSON 对象的日志格式"""
+
+    def format(self, record: logging.LogRecord) -> str:
+        entry = {
+            "time": datetime.fromtimestamp(record.created).isoformat(timespec="milliseconds"),
+            "level": record.levelname,
+            "logger": record.name,
+            "message": record.getMessage(),
+            **getattr(record, "context", {}),
+        }
+        if record.exc_info:
+            entry["exception"] = self.formatException(record.exc_info)
+        return json.dumps(entry, ensure_ascii=False, default=str)
+
+
+TEXT_FORMAT = '%(asctime)s | %(levelname)-8s | %(name)s -%(context_text)s %(message)s'
+
+
+def configure_logging(force: bool = False) -> None:
+    """按 app_config 安装控制台与滚动文件日志（log_level / log_format / 保留份数）"""
+    global _handlers_installed
+    if _handlers_installed and not force:
+        return
+
+    log_dir = app_config.logs_dir
+    log_dir.mkdir(parents=True, exist_ok=True)
+
+    root = logging.getLogger()
+    for handler in list(root.handlers):
+        root.removeHandler(handler)
+        handler.close()
+
+    file_handler = logging.handlers.RotatingFileHandler(
+        log_dir / app_config.log_file,
+        maxBytes=int(app_config.log_max_size_mb * 1024 * 1024),
+        backupCount=app_config.log_backup_count,
+        encoding='utf-8',
+    )
+    console_handler = logging.StreamHandler(sys.stdout)
+    text_formatter = logging.Formatter(TEXT_FORMAT)
+    file_handler.setFormatter(JsonFormatter() if app_config.log_format == "json" else text_formatter)
+    console_handler.setFormatter(text_formatter)
+
+    for handler in (console_handler, file_handler):
+        handler.addFilter(ContextFilter())
+        root.addHandler(handler)
+    root.setLevel(app_config.log_level.value)
+    _handlers_installed = True
+
+
+def prompt_for_log(text: Optional[str]) -> str:
+    """按 log_redact_prompts 返回提示词原文，或仅返回长度与摘要"""
+    text = text or ""
+    if not app_config.log_redact_prompts:
+        return text
+    digest = hashlib.sha256(text.encode("utf-8")).hexdigest()[:12]
+    return f"<redacted {len(text)} chars sha256:{digest}>"
+
 
 # 简化的日志系统
 class SimpleLogger:
@@ -19,20 +124,7 @@ class SimpleLogger:
 
     def setup_logging(self):
         """设置日志"""
-        # 创建日志目录
-        log_dir = Path("data/logs")
-        log_dir.mkdir(parents=True, exist_ok=True)
-
-        # 配置标准库日志
-        logging.basicConfig(
-            level=logging.INFO,
-            format='%(asctime)s | %(levelname)-8s | %(name)s - %(message)s',
-            handlers=[
-                logging.StreamHandler(sys.stdout),
-                logging.FileHandler(log_dir / "ideaarchitect.log", encoding='utf-8')
-            ]
-        )
-
+        configure_logging()
         self.logger = logging.getLogger(self.name)
 
     def info(self, message: str):
@@ -57,8 +149,9 @@ _global_logger = SimpleLogger()
 
 
 def setup_logging():
-    """设置日志系统"""
+    """设置日志系统（重新读取日志配置）"""
     global _global_logger
+    configure_logging(force=True)
     _global_logger = SimpleLogger()
     _global_logger.info("日志系统初始化完成")
 
@@ -0,0 +1,64 @@
+"""
+Mock Provider
+不发起网络请求、按智能体返回固定且符合输出格式的响应的模拟提供商，用于 CI 与演示；
+通过 models.provider = "mock"（config.toml）、MODEL_PROVIDER=mock 或设置 default_model_provider 选择
+"""
+
+import json
+from pathlib import Path
+from typing import Dict, Any, Optional
+
+from .logger import get_logger, get_log_context
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+MOCK_PROVIDER = "mock"
+MOCK_MODEL = "mock-1"
+
+# 内置响应：clarifier 与 summarizer 与真实模型被要求返回的 JSON 结构一致
+DEFAULT_RESPONSES: Dict[str, Dict[str, Any]] = {
+    "clarifier": {
+        "questions": [
+            {"question": "Who is the primary target user?", "type": "target", "priority": 10, "slot_name": "target_user"},
+            {"question": "What core problem does it solve for them?", "type": "problem", "priority": 9, "slot_name": "core_pain"},
+            {"question": "What are the must-have features for the first version?", "type": "scope", "priority": 8, "slot_name": "key_features"},
+            {"question": "How will you measure success?", "type": "metrics", "priority": 7, "slot_name": "success_metrics"},
+            {"question": "What constraints apply (budget, timeline, compliance)?", "type": "constraints", "priority": 6, "slot_name": "constraints"},
+        ]
+    },
+    "summarizer": {
+        "title": "Mock summary",
+        "refined_idea": "A deterministic summary produced by the mock provider.",
+        "user_segments": ["Early adopters"],
+        "core_pain_points": ["Existing tools are slow"],
+        "key_features": ["Guided clarification", "Structured summary"],
+        "constraints": ["Small team"],
+        "success_metrics": ["Weekly active users"],
+        "risks": ["Unclear willingness to pay"],
+        "next_steps": ["Interview five target users"],
+    },
+}
+
+GENERIC_RESPONSE: Dict[str, Any] = {"content": "Mock response."}
+
+
+class MockProvider:
+    """按智能体返回固定响应；fixtures_dir 下的 <智能体>.json 覆盖内置响应"""
+
+    def __init__(self, fixtures_dir: Optional[Path] = None):
+        self.fixtures_dir = fixtures_dir if fixtures_dir is not None else app_config.mock_fixtures_dir
+
+    def response(self, agent: Optional[str]) -> Dict[str, Any]:
+        if agent and self.fixtures_dir:
+            fixture = Path(self.fixtures_dir) / f"{agent}.json"
+            if fixture.exists():
+                with open(fixture, 'r', encoding='utf-8') as f:
+                    return json.load(f)
+        return DEFAULT_RESPONSES.get(agent or "", GENERIC_RESPONSE)
+
+    def complete(self, system_prompt: Optional[str], user_content: str, agent: Optional[str] = None) -> str:
+        """返回 JSON 文本；未指定智能体时取 log_context 中的 agent"""
+        agent = agent or get_log_context().get("agent")
+        logger.debug(f"模拟提供商响应 agent={agent}")
+        return json.dumps(self.response(agent), ensure_ascii=False)
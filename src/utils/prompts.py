@@ -0,0 +1,87 @@
+"""
+Prompt Templates
+内置提示词模板，可被数据目录 prompts/ 下的同名 .txt 文件覆盖
+
+模板使用 string.Template 语法（$idea、${domain}），可用变量：
+- idea：用户输入的原始想法
+- domain：想法所属领域（未指定时为 unspecified）
+- context_hints：上下文提示（以逗号连接，没有时为 none）
+- enriched_idea：原始想法 + 澄清问答（仅总结阶段）
+- domain_slots：匹配到领域模板时，建议覆盖的槽位与问题（否则为空）
+- domain_fields：匹配到领域模板时，要求总结额外输出的领域字段说明（否则为空）
+- language_instruction：设置了输出语言时的语言要求（否则为空）
+"""
+
+from string import Template
+from typing import Dict, List, Any, Optional
+
+from ..utils.logger import get_logger
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+
+DEFAULT_PROMPTS: Dict[str, str] = {
+    "clarifier.system": (
+        "You generate a concise list of 6-10 clarification questions for the idea. "
+        "Return strict JSON with fields: questions:[{question, type, priority(1-10), slot_name}]"
+        "${language_instruction}"
+    ),
+    "clarifier.user": "Idea: $idea\nDomain: ${domain}\nContext: ${context_hints}\n${domain_slots}Return JSON only.",
+    "summarizer.system": (
+        "You are a product strategist. Summarize and refine the idea based on the given enriched idea (original idea + Q&A). "
+        "Return strict JSON with keys: title, refined_idea, user_segments[], core_pain_points[], key_features[], constraints[], success_metrics[], risks[], next_steps[]"
+        "${domain_fields}${language_instruction}"
+    ),
+    "summarizer.user": "Domain: ${domain}\nContext: ${context_hints}\n\n$enriched_idea",
+}
+
+_overrides: Optional[Dict[str, str]] = None
+
+
+def prompts_dir():
+    return app_config.data_dir / "prompts"
+
+
+def load_prompt_overrides() -> Dict[str, str]:
+    """读取 prompts/<name>.txt 覆盖文件（仅识别内置模板名称）"""
+    global _overrides
+    overrides: Dict[str, str] = {}
+    directory = prompts_dir()
+    if directory.is_dir():
+        for name in DEFAULT_PROMPTS:
+            prompt_file = directory / f"{name}.txt"
+            if prompt_file.exists():
+                try:
+                    overrides[name] = prompt_file.read_text(encoding="utf-8").strip()
+                except Exception as e:
+                    logger.warning(f"读取提示词文件失败 {prompt_file}: {e}")
+    _overrides = overrides
+    return overrides
+
+
+def get_prompt_template(name: str) -> str:
+    if _overrides is None:
+        load_prompt_overrides()
+    return (_overrides or {}).get(name) or DEFAULT_PROMPTS[name]
+
+
+def render_prompt(name: str, variables: Dict[str, Any]) -> str:
+    """渲染模板；未提供的变量保持原样"""
+    return Template(get_prompt_template(name)).safe_substitute(
+        {key: "" if value is None else str(value) for key, value in variables.items()}
+    )
+
+
+def list_prompts() -> List[Dict[str, Any]]:
+    if _overrides is None:
+        load_prompt_overrides()
+    return [
+        {
+            "name": name,
+            "overridden": name in (_overrides or {}),
+            "path": str(prompts_dir() / f"{name}.txt"),
+            "template": get_prompt_template(name),
+        }
+        for name in DEFAULT_PROMPTS
+    ]
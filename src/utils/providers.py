@@ -0,0 +1,955 @@
+"""
+AI Provider Utilities
+各模型提供商的密钥、基础地址与最小化请求封装
+"""
+
+import hashlib
+import json
+import os
+import random
+import time
+from datetime import datetime, timedelta, timezone
+from email.utils import parsedate_to_datetime
+from enum import Enum
+from typing import Callable, Dict, Iterator, List, Any, Optional, Tuple
+from urllib.parse import urlencode, urlsplit
+
+import requests
+
+from ..utils.logger import get_logger, get_log_context, prompt_for_log
+from ..utils.audit import record_audit
+from ..utils.storage import CacheStorage
+from ..utils.safety import confidential_terms, redact_text
+from ..utils.ratelimit import RateLimitTimeout, acquire_provider_slot, estimate_tokens
+from ..utils.recording import load_recording, recording_path, save_recording
+from ..utils.mock_provider import MOCK_MODEL, MOCK_PROVIDER, MockProvider
+from config.app_config import app_config, AZURE_DEFAULT_API_VERSION
+
+logger = get_logger(__name__)
+
+
+# 提供商运行时设置：密钥环境变量（按优先级）、基础地址、默认模型、请求风格
+PROVIDER_SETTINGS: Dict[str, Dict[str, Any]] = {
+    "openai": {
+        "label": "OpenAI",
+        "key_envs": ["OPENAI_API_KEY"],
+        "base_env": "OPENAI_API_BASE",
+        "default_base": "https://api.openai.com",
+        "model_env": "OPENAI_MODEL",
+        "default_model": "gpt-4o-mini",
+        "version_env": "OPENAI_API_VERSION",
+        "style": "openai",
+        "key_prefix": "sk-",
+    },
+    "anthropic": {
+        "label": "Anthropic",
+        "key_envs": ["ANTHROPIC_API_KEY"],
+        "base_env": "ANTHROPIC_API_BASE",
+        "default_base": "https://api.anthropic.com",
+        "model_env": "ANTHROPIC_MODEL",
+        "default_model": "claude-3-5-sonnet-20240620",
+        "style": "anthropic",
+        "key_prefix": "sk-ant-",
+    },
+    "deepseek": {
+        "label": "DeepSeek",
+        "key_envs": ["DEEPSEEK_API_KEY"],
+        "base_env": "DEEPSEEK_API_BASE",
+        "default_base": "https://api.deepseek.com",
+        "model_env": "DEEPSEEK_MODEL",
+        "default_model": "deepseek-chat",
+        "style": "openai",
+        "key_prefix": "sk-",
+    },
+    "qwen": {
+        "label": "Qwen(DashScope)",
+        "key_envs": ["DASHSCOPE_API_KEY", "QWEN_API_KEY"],
+        "base_env": "QWEN_API_BASE",
+        "default_base": "https://dashscope.aliyuncs.com/compatible-mode",
+        "model_env": "QWEN_MODEL",
+        "default_model": "qwen-plus",
+        "style": "openai",
+        "key_prefix": "sk-",
+    },
+    "gemini": {
+        "label": "Google Gemini",
+        "key_envs": ["GEMINI_API_KEY", "GOOGLE_API_KEY"],
+        "base_env": "GEMINI_API_BASE",
+        "default_base": "https://generativelanguage.googleapis.com",
+        "model_env": "GEMINI_MODEL",
+        "default_model": "gemini-1.5-flash",
+        "style": "gemini",
+        "key_prefix": "AIza",
+    },
+    # 模拟提供商：不需要密钥，只在被选中（mock_provider_selected）或按会话指定时使用
+    MOCK_PROVIDER: {
+        "label": "Mock",
+        "key_envs": [],
+        "base_env": "MOCK_API_BASE",
+        "default_base": "mock://local",
+        "model_env": "MOCK_MODEL",
+        "default_model": MOCK_MODEL,
+        "style": "mock",
+        "key_prefix": "",
+    },
+}
+
+# 单次重试等待的上限（秒）
+MAX_RETRY_WAIT = 30.0
+
+
+class ModelErrorKind(str, Enum):
+    """模型调用失败的类型，调用方据此决定重试或切换提供商"""
+    RATE_LIMITED = "rate_limited"
+    TIMEOUT = "timeout"
+    INVALID_KEY = "invalid_key"
+    CONTEXT_TOO_LONG = "context_too_long"
+    UNAVAILABLE = "unavailable"
+    BAD_REQUEST = "bad_request"
+    BAD_RESPONSE = "bad_response"
+
+
+# 可重试的失败类型
+RETRYABLE_ERRORS = {ModelErrorKind.RATE_LIMITED, ModelErrorKind.TIMEOUT, ModelErrorKind.UNAVAILABLE}
+
+
+class ModelError(Exception):
+    """带类型的模型调用错误"""
+
+    def __init__(self, kind: ModelErrorKind, message: str, provider: Optional[str] = None,
+                 status_code: Optional[int] = None, retry_after: Optional[float] = None):
+        super().__init__(message)
+        self.kind = kind
+        self.provider = provider
+        self.status_code = status_code
+        self.retry_after = retry_after
+
+    @property
+    def retryable(self) -> bool:
+        return self.kind in RETRYABLE_ERRORS
+
+    def __str__(self) -> str:
+        return f"[{self.kind.value}] {super().__str__()}"
+
+
+# 模型元数据：上下文窗口（tokens）与参考价格（美元 / 百万 tokens，输入/输出），按前缀匹配，越具体越靠前
+MODEL_METADATA: List[Tuple[str, Dict[str, Any]]] = [
+    ("gpt-4o-mini", {"context_window": 128000, "input_price": 0.15, "output_price": 0.6}),
+    ("gpt-4o", {"context_window": 128000, "input_price": 2.5, "output_price": 10.0}),
+    ("gpt-4-turbo", {"context_window": 128000, "input_price": 10.0, "output_price": 30.0}),
+    ("gpt-4", {"context_window": 8192, "input_price": 30.0, "output_price": 60.0}),
+    ("gpt-3.5-turbo", {"context_window": 16385, "input_price": 0.5, "output_price": 1.5}),
+    ("claude-3-5-sonnet", {"context_window": 200000, "input_price": 3.0, "output_price": 15.0}),
+    ("claude-3-5-haiku", {"context_window": 200000, "input_price": 0.8, "output_price": 4.0}),
+    ("claude-3-opus", {"context_window": 200000, "input_price": 15.0, "output_price": 75.0}),
+    ("claude-3-sonnet", {"context_window": 200000, "input_price": 3.0, "output_price": 15.0}),
+    ("claude-3-haiku", {"context_window": 200000, "input_price": 0.25, "output_price": 1.25}),
+    ("deepseek-chat", {"context_window": 64000, "input_price": 0.27, "output_price": 1.1}),
+    ("deepseek-reasoner", {"context_window": 64000, "input_price": 0.55, "output_price": 2.19}),
+    ("qwen-turbo", {"context_window": 1000000, "input_price": 0.05, "output_price": 0.2}),
+    ("qwen-plus", {"context_window": 131072, "input_price": 0.4, "output_price": 1.2}),
+    ("qwen-max", {"context_window": 32768, "input_price": 1.6, "output_price": 6.4}),
+    ("gemini-1.5-pro", {"context_window": 2097152, "input_price": 1.25, "output_price": 5.0}),
+    ("gemini-1.5-flash", {"context_window": 1048576, "input_price": 0.075, "output_price": 0.3}),
+]
+
+# 本地 Ollama 服务（无需密钥，仅用于列出已下载的模型）
+OLLAMA_BASE_ENV = "OLLAMA_API_BASE"
+OLLAMA_DEFAULT_BASE = "http://localhost:11434"
+
+# 模型列表缓存时间
+MODEL_LIST_TTL = timedelta(hours=6)
+
+# 按模型名前缀推断提供商
+MODEL_PREFIXES = [
+    ("gpt-", "openai"),
+    ("o1", "openai"),
+    ("claude-", "anthropic"),
+    ("deepseek-", "deepseek"),
+    ("qwen", "qwen"),
+    ("gemini-", "gemini"),
+]
+
+
+def normalize_api_base(base: str, strip_v1: bool = True) -> str:
+    if not base:
+        return base
+    b = base.strip().rstrip('/')
+    if strip_v1 and b.endswith('/v1'):
+        b = b[:-3]
+    return b
+
+
+def bootstrap_env_from_toml() -> None:
+    """If env vars are missing, try load from MAgent/config.toml and set os.environ accordingly.
+    Only sets variables that are currently missing.
+    """
+    try:
+        import tomllib  # Python 3.11+
+        # __file__ -> .../MAgent/src/utils/providers.py; go up to MAgent/
+        here = os.path.dirname(os.path.abspath(__file__))
+        magent_dir = os.path.abspath(os.path.join(here, '..', '..'))
+        cfg_path = os.path.join(magent_dir, 'config.toml')
+        if not os.path.exists(cfg_path):
+            return
+        with open(cfg_path, 'rb') as f:
+            data = tomllib.load(f)
+        ai = data.get('ai', {}) if isinstance(data, dict) else {}
+        # Map keys -> env if missing
+        mapping: List[Tuple[str, str]] = [
+            ('OPENAI_API_KEY', ai.get('openai_api_key') or ''),
+            ('ANTHROPIC_API_KEY', ai.get('claude_api_key') or ''),
+            ('DEEPSEEK_API_KEY', ai.get('deepseek_api_key') or ''),
+            ('QWEN_API_KEY', ai.get('qwen_api_key') or ''),
+            ('GEMINI_API_KEY', ai.get('gemini_api_key') or ''),
+        ]
+        for env_name, value in mapping:
+            if value and not os.getenv(env_name):
+                os.environ[env_name] = value
+        # Normalize and set OPENAI_API_BASE if provided in toml
+        models = data.get('models', {}) if isinstance(data, dict) else {}
+        if isinstance(models.get('provider'), str) and not os.getenv('MODEL_PROVIDER'):
+            os.environ['MODEL_PROVIDER'] = models['provider']
+        openai_base = ai.get('openai_base_url') or ''
+        if openai_base and not os.getenv('OPENAI_API_BASE'):
+            os.environ['OPENAI_API_BASE'] = normalize_api_base(openai_base, strip_v1=True)
+    except Exception as e:
+        logger.debug(f"config.toml bootstrap skipped or failed: {e}")
+
+
+def get_provider_settings(provider: str) -> Optional[Dict[str, Any]]:
+    """解析提供商的密钥、基础地址与默认模型；未配置密钥或处于离线模式时返回 None
+
+    回放模式下，存在该提供商的录制文件即视为已配置（不需要真实密钥）。
+    """
+    spec = PROVIDER_SETTINGS.get(provider)
+    if not spec or app_config.offline:
+        return None
+    bootstrap_env_from_toml()
+    api_key = next((os.getenv(name) for name in spec["key_envs"] if os.getenv(name)), None)
+    if spec["style"] == "mock":
+        api_key = "mock"
+    if not api_key and app_config.vcr_mode == "replay" and any(app_config.vcr_dir.glob(f"{provider}-*.json")):
+        api_key = "replay"
+    if not api_key:
+        return None
+    strip_v1 = spec["style"] == "openai"
+    return {
+        "provider": provider,
+        "api_key": api_key,
+        "base": normalize_api_base(os.getenv(spec["base_env"]) or spec["default_base"], strip_v1=strip_v1),
+        "model": os.getenv(spec["model_env"], spec["default_model"]),
+        "api_version": os.getenv(spec["version_env"]) if spec.get("version_env") else None,
+        "style": spec["style"],
+        "label": spec["label"],
+    }
+
+
+def mock_provider_selected() -> bool:
+    """config.toml 的 models.provider、MODEL_PROVIDER 环境变量或 default_model_provider 设置为 mock"""
+    bootstrap_env_from_toml()
+    return (os.getenv("MODEL_PROVIDER") or app_config.default_model_provider) == MOCK_PROVIDER
+
+
+def configured_providers() -> List[str]:
+    """返回已配置密钥的提供商名称；选中模拟提供商时只返回它"""
+    if mock_provider_selected() and not app_config.offline:
+        return [MOCK_PROVIDER]
+    return [name for name in PROVIDER_SETTINGS if name != MOCK_PROVIDER and get_provider_settings(name)]
+
+
+def provider_for_model(model: str) -> Optional[str]:
+    """根据模型名称（如 gemini-1.5-pro、gpt-4o-mini）推断提供商"""
+    name = (model or "").strip().lower()
+    for prefix, provider in MODEL_PREFIXES:
+        if name.startswith(prefix):
+            return provider
+    return None
+
+
+def is_azure_endpoint(base: str) -> bool:
+    """Azure OpenAI 资源地址或部署地址"""
+    host = urlsplit(base or "").hostname or ""
+    return host.endswith(".openai.azure.com") or "/openai/deployments/" in (base or "")
+
+
+def resolve_endpoint(settings: Dict[str, Any], model: str) -> Tuple[str, str, Optional[str]]:
+    """返回实际请求使用的基础地址、密钥与 api-version；优先使用 model_endpoints 中的按模型覆盖"""
+    override = app_config.model_endpoints.get(model)
+    if override:
+        base = normalize_api_base(override.base_url, strip_v1=settings["style"] == "openai")
+        return base, override.api_key or settings["api_key"], override.api_version or settings.get("api_version")
+    return settings["base"], settings["api_key"], settings.get("api_version")
+
+
+def _openai_target(base: str, api_key: str, api_version: Optional[str], model: str) -> Tuple[str, Dict[str, str]]:
+    """OpenAI 兼容接口的 URL 与请求头；Azure 使用部署路径与 api-key 请求头"""
+    if is_azure_endpoint(base):
+        if "/openai/deployments/" in base:
+            url = base + '/chat/completions'
+        else:
+            url = f"{base}/openai/deployments/{model}/chat/completions"
+        url += '?' + urlencode({"api-version": api_version or AZURE_DEFAULT_API_VERSION})
+        return url, {"api-key": api_key, "Content-Type": "application/json"}
+    url = base + '/v1/chat/completions'
+    if api_version:
+        url += '?' + urlencode({"api-version": api_version})
+    return url, {"Authorization": f"Bearer {api_key}", "Content-Type": "application/json"}
+
+
+def _build_chat_request(
+    settings: Dict[str, Any],
+    model: str,
+    system_prompt: Optional[str],
+    user_content: str,
+    temperature: Optional[float] = None,
+    max_tokens: Optional[int] = None,
+    top_p: Optional[float] = None,
+    json_mode: bool = False,
+) -> Tuple[str, Dict[str, str], Dict[str, Any]]:
+    """按提供商请求风格组装 URL、请求头与请求体
+
+    json_mode 使用提供商原生的 JSON 输出约束：OpenAI 兼容接口的 response_format、
+    Gemini 的 responseMimeType；Anthropic 通过预填充 "{" 让回复从 JSON 对象开始。
+    """
+    style = settings["style"]
+    base, api_key, api_version = resolve_endpoint(settings, model)
+    if style == "anthropic":
+        url = base + '/v1/messages'
+        headers = {
+            "x-api-key": api_key,
+            "anthropic-version": "2023-06-01",
+            "content-type": "application/json",
+        }
+        body: Dict[str, Any] = {
+            "model": model,
+            "max_tokens": max_tokens or 1024,
+            "messages": [{"role": "user", "content": user_content}],
+        }
+        if system_prompt:
+            body["system"] = system_prompt
+        if json_mode:
+            body["messages"].append({"role": "assistant", "content": "{"})
+    elif style == "gemini":
+        url = f"{base}/v1beta/models/{model}:generateContent"
+        headers = {"x-goog-api-key": api_key, "Content-Type": "application/json"}
+        body = {"contents": [{"role": "user", "parts": [{"text": user_content}]}]}
+        if system_prompt:
+            body["systemInstruction"] = {"parts": [{"text": system_prompt}]}
+        generation_config: Dict[str, Any] = {}
+        if max_tokens is not None:
+            generation_config["maxOutputTokens"] = max_tokens
+        if temperature is not None:
+            generation_config["temperature"] = temperature
+        if top_p is not None:
+            generation_config["topP"] = top_p
+        if json_mode:
+            generation_config["responseMimeType"] = "application/json"
+        if generation_config:
+            body["generationConfig"] = generation_config
+        return url, headers, body
+    else:
+        url, headers = _openai_target(base, api_key, api_version, model)
+        messages = [{"role": "system", "content": system_prompt}] if system_prompt else []
+        messages.append({"role": "user", "content": user_content})
+        body = {"model": model, "messages": messages}
+        if max_tokens is not None:
+            body["max_tokens"] = max_tokens
+        # OpenAI 要求消息中出现 "json" 字样才能启用 json_object
+        if json_mode and "json" in f"{system_prompt or ''}{user_content}".lower():
+            body["response_format"] = {"type": "json_object"}
+    if temperature is not None:
+        body["temperature"] = temperature
+    if top_p is not None:
+        body["top_p"] = top_p
+    return url, headers, body
+
+
+def _extract_text(style: str, data: Dict[str, Any]) -> str:
+    if style == "anthropic":
+        # Anthropic content can be array with text
+        return ''.join(part.get('text', '') for part in data.get('content', []) if isinstance(part, dict))
+    if style == "gemini":
+        candidates = data.get('candidates') or []
+        if not candidates:
+            reason = (data.get('promptFeedback') or {}).get('blockReason')
+            raise ValueError(f"Gemini 未返回候选结果{f'（{reason}）' if reason else ''}")
+        parts = (candidates[0].get('content') or {}).get('parts') or []
+        return ''.join(part.get('text', '') for part in parts if isinstance(part, dict))
+    return data['choices'][0]['message']['content']
+
+
+def _extract_usage(style: str, data: Dict[str, Any]) -> Dict[str, Optional[int]]:
+    """响应中提供商报告的 token 用量；未返回时为 None"""
+    if style == "gemini":
+        usage = data.get('usageMetadata') or {}
+        return {"input_tokens": usage.get('promptTokenCount'), "output_tokens": usage.get('candidatesTokenCount')}
+    usage = data.get('usage') or {}
+    if style == "anthropic":
+        return {"input_tokens": usage.get('input_tokens'), "output_tokens": usage.get('output_tokens')}
+    return {"input_tokens": usage.get('prompt_tokens'), "output_tokens": usage.get('completion_tokens')}
+
+
+def _record_model_call(
+    provider: str,
+    model: str,
+    attempt: int,
+    started_at: datetime,
+    started: float,
+    error: Optional["ModelError"] = None,
+    **fields: Any,
+) -> None:
+    """在审计日志中记录一次模型请求（重试的每次尝试各一条），供会话追踪统计耗时、token、重试与回退"""
+    record_audit(
+        "model_call",
+        provider,
+        success=error is None,
+        agent=get_log_context().get("agent"),
+        model=model,
+        attempt=attempt,
+        started_at=started_at.isoformat(),
+        duration_ms=int((time.monotonic() - started) * 1000),
+        error_code=error.kind.value if error else None,
+        **fields,
+    )
+
+
+def ping_provider(provider: str, model: Optional[str] = None, timeout: float = 10) -> Dict[str, Any]:
+    """向提供商发送一次最小化对话请求，返回是否成功、延迟与错误信息"""
+    settings = get_provider_settings(provider)
+    if not settings:
+        error = "离线模式下不发起请求" if app_config.offline else "未配置API密钥"
+        return {"provider": provider, "success": False, "latency_ms": None, "error": error}
+
+    model = model or settings["model"]
+    if settings["style"] == "mock":
+        return {"provider": provider, "model": model, "success": True, "latency_ms": 0, "error": None}
+    url, headers, body = _build_chat_request(settings, model, None, "ping", max_tokens=1)
+
+    started = time.monotonic()
+    try:
+        resp = requests.post(url, headers=headers, json=body, timeout=timeout)
+        latency_ms = int((time.monotonic() - started) * 1000)
+        if resp.status_code >= 400:
+            return {
+                "provider": provider,
+                "model": model,
+                "success": False,
+                "latency_ms": latency_ms,
+                "error": f"HTTP {resp.status_code}: {resp.text[:200]}",
+            }
+        return {"provider": provider, "model": model, "success": True, "latency_ms": latency_ms, "error": None}
+    except requests.RequestException as e:
+        return {
+            "provider": provider,
+            "model": model,
+            "success": False,
+            "latency_ms": int((time.monotonic() - started) * 1000),
+            "error": str(e),
+        }
+
+
+def parse_retry_after(value: Optional[str]) -> Optional[float]:
+    """解析 Retry-After 响应头（秒数或 HTTP 日期）"""
+    if not value:
+        return None
+    try:
+        return max(0.0, float(value))
+    except ValueError:
+        pass
+    try:
+        when = parsedate_to_datetime(value)
+    except (TypeError, ValueError):
+        return None
+    if when.tzinfo is None:
+        when = when.replace(tzinfo=timezone.utc)
+    return max(0.0, (when - datetime.now(timezone.utc)).total_seconds())
+
+
+def classify_response(provider: str, resp: requests.Response) -> ModelError:
+    """将失败的 HTTP 响应归类为 ModelError"""
+    status = resp.status_code
+    text = resp.text[:500]
+    lowered = text.lower()
+    message = f"HTTP {status}: {text[:200]}"
+    if status == 429:
+        retry_after = parse_retry_after(resp.headers.get("Retry-After"))
+        return ModelError(ModelErrorKind.RATE_LIMITED, message, provider, status, retry_after)
+    if status in (401, 403) or "api_key_invalid" in lowered:
+        return ModelError(ModelErrorKind.INVALID_KEY, message, provider, status)
+    if status in (400, 413) and any(k in lowered for k in ("context_length", "context length", "too long", "maximum context", "too many tokens")):
+        return ModelError(ModelErrorKind.CONTEXT_TOO_LONG, message, provider, status)
+    if status in (408, 504):
+        return ModelError(ModelErrorKind.TIMEOUT, message, provider, status)
+    if status >= 500 or status == 529:
+        return ModelError(ModelErrorKind.UNAVAILABLE, message, provider, status)
+    return ModelError(ModelErrorKind.BAD_REQUEST, message, provider, status)
+
+
+# 接口拒绝 JSON 输出约束时错误信息中出现的字段（OpenAI 兼容接口的 response_format、Gemini 的 responseMimeType）
+JSON_MODE_ERROR_MARKERS = ("response_format", "json", "response_mime_type", "responsemimetype")
+
+
+def _rejects_json_mode(error: ModelError) -> bool:
+    """只有 400 且错误信息指向 JSON 输出约束时才视为不支持 json_mode；密钥、模型不存在、额度等错误不重发"""
+    if error.kind != ModelErrorKind.BAD_REQUEST or error.status_code != 400:
+        return False
+    message = str(error).lower()
+    return any(marker in message for marker in JSON_MODE_ERROR_MARKERS)
+
+
+def _backoff_delay(attempt: int, base_delay: float, error: ModelError) -> float:
+    """带抖动的指数退避；服务端给出 Retry-After 时以其为准"""
+    if error.retry_after is not None:
+        return min(error.retry_after, MAX_RETRY_WAIT)
+    return min(base_delay * (2 ** attempt) * random.uniform(0.5, 1.5), MAX_RETRY_WAIT)
+
+
+def _post_once(provider: str, url: str, headers: Dict[str, str], body: Dict[str, Any], timeout: float) -> Dict[str, Any]:
+    try:
+        resp = requests.post(url, headers=headers, json=body, timeout=timeout)
+    except requests.Timeout as e:
+        raise ModelError(ModelErrorKind.TIMEOUT, str(e), provider) from e
+    except requests.RequestException as e:
+        raise ModelError(ModelErrorKind.UNAVAILABLE, str(e), provider) from e
+    if resp.status_code >= 400:
+        raise classify_response(provider, resp)
+    try:
+        return resp.json()
+    except ValueError as e:
+        raise ModelError(ModelErrorKind.BAD_RESPONSE, f"响应不是有效的 JSON: {e}", provider, resp.status_code) from e
+
+
+_response_cache: Optional[CacheStorage] = None
+
+
+def _get_response_cache() -> CacheStorage:
+    global _response_cache
+    if _response_cache is None:
+        _response_cache = CacheStorage()
+    return _response_cache
+
+
+def response_cache_key(url: str, body: Dict[str, Any]) -> str:
+    """按请求地址与请求体（模型、消息、生成参数）计算缓存键；不包含密钥"""
+    payload = json.dumps({"url": url, "body": body}, sort_keys=True, ensure_ascii=False)
+    return "llm_" + hashlib.sha256(payload.encode("utf-8")).hexdigest()
+
+
+def chat_completion(
+    settings: Dict[str, Any],
+    system_prompt: str,
+    user_content: str,
+    model: Optional[str] = None,
+    temperature: Optional[float] = None,
+    max_tokens: Optional[int] = None,
+    top_p: Optional[float] = None,
+    timeout: float = 30,
+    max_retries: Optional[int] = None,
+    json_mode: bool = False,
+    use_cache: bool = False,
+    deadline: Optional[float] = None,
+) -> str:
+    """按提供商请求风格发送对话请求并返回文本内容
+
+    限流、超时与服务端错误按 app_config.max_retries / retry_delay 指数退避重试；
+    最终失败时抛出 ModelError。json_mode 被接口拒绝（如不支持 response_format 的网关）时
+    自动去掉约束再请求一次。use_cache 为真且 response_cache_enabled 时，
+    相同请求在 response_cache_ttl_hours 内直接返回缓存结果。deadline（time.monotonic 时间）
+    限制包括重试等待在内的总耗时，到期后抛出 timeout 类型的 ModelError。
+    每次发送（包括重试）前按 provider_rate_limits 等待限流额度。
+    vcr_mode 为 record 时把响应写入夹具文件，为 replay 时只从夹具文件读取、不发起请求
+    （两种模式下都不使用响应缓存）。
+    """
+    provider = settings["provider"]
+    model = model or settings["model"]
+    if settings["style"] == "mock":
+        text = MockProvider().complete(system_prompt, user_content)
+        _record_model_call(provider, model, 0, datetime.now(), time.monotonic(), json_mode=json_mode)
+        return text
+    url, headers, body = _build_chat_request(
+        settings, model, system_prompt, user_content,
+        temperature=temperature, max_tokens=max_tokens, top_p=top_p, json_mode=json_mode,
+    )
+    logger.debug(f"{settings['label']} model={model} system={prompt_for_log(system_prompt)} user={prompt_for_log(user_content)}")
+    cache_key = None
+    if use_cache and app_config.response_cache_enabled and app_config.vcr_mode == "off":
+        cache_key = response_cache_key(url, body)
+        cached = _get_response_cache().get(cache_key)
+        if cached is not None:
+            logger.info(f"{settings['label']} 命中响应缓存 model={model}")
+            _record_model_call(provider, model, 0, datetime.now(), time.monotonic(), cached=True, json_mode=json_mode)
+            return cached
+    retries = app_config.max_retries if max_retries is None else max_retries
+    tokens = estimate_tokens(system_prompt, user_content, max_tokens=max_tokens)
+    attempt = 0
+    while True:
+        attempt_started_at, attempt_started = datetime.now(), time.monotonic()
+        try:
+            if app_config.vcr_mode == "replay":
+                data = load_recording(provider, url, body)
+                if data is None:
+                    raise ModelError(
+                        ModelErrorKind.BAD_RESPONSE,
+                        f"回放模式下没有录制的响应: {recording_path(provider, url, body).name}",
+                        provider,
+                    )
+            else:
+                try:
+                    acquire_provider_slot(provider, tokens, deadline)
+                except RateLimitTimeout as e:
+                    raise ModelError(ModelErrorKind.TIMEOUT, str(e), provider) from e
+                request_timeout = timeout
+                if deadline is not None:
+                    remaining = deadline - time.monotonic()
+                    if remaining <= 0:
+                        raise ModelError(ModelErrorKind.TIMEOUT, "超出智能体总时限", provider)
+                    request_timeout = min(timeout, remaining)
+                data = _post_once(provider, url, headers, body, request_timeout)
+                if app_config.vcr_mode == "record":
+                    save_recording(provider, url, body, data)
+            try:
+                text = _extract_text(settings["style"], data)
+            except (KeyError, IndexError, TypeError, ValueError) as e:
+                raise ModelError(ModelErrorKind.BAD_RESPONSE, f"无法解析响应内容: {e}", provider) from e
+            if json_mode and settings["style"] == "anthropic":
+                # 补回预填充的 "{"
+                text = "{" + text
+            if cache_key:
+                # 缓存文件中不保留密钥、邮箱与保密词
+                cached_text = redact_text(text, confidential_terms())
+                _get_response_cache().set(cache_key, cached_text, ttl=timedelta(hours=app_config.response_cache_ttl_hours))
+            _record_model_call(provider, model, attempt, attempt_started_at, attempt_started,
+                               json_mode=json_mode, **_extract_usage(settings["style"], data))
+            return text
+        except ModelError as e:
+            _record_model_call(provider, model, attempt, attempt_started_at, attempt_started, e, json_mode=json_mode)
+            if json_mode and _rejects_json_mode(e):
+                logger.warning(f"{settings['label']} 不支持 JSON 输出模式，改为普通请求: {e}")
+                return chat_completion(
+                    settings, system_prompt, user_content, model=model, temperature=temperature,
+                    max_tokens=max_tokens, top_p=top_p, timeout=timeout, max_retries=max_retries,
+                    use_cache=use_cache, deadline=deadline,
+                )
+            if not e.retryable or attempt >= retries:
+                raise
+            delay = _backoff_delay(attempt, app_config.retry_delay, e)
+            if deadline is not None and time.monotonic() + delay >= deadline:
+                raise
+            attempt += 1
+            logger.warning(f"{settings['label']} 请求失败 {e}，{delay:.1f}s 后第 {attempt}/{retries} 次重试")
+            time.sleep(delay)
+
+
+def _iter_sse(provider: str, resp: requests.Response) -> Iterator[Dict[str, Any]]:
+    """逐个解析 SSE 响应中的 data 事件；流中返回的错误事件转为 ModelError"""
+    for line in resp.iter_lines(decode_unicode=True):
+        if not line or not line.startswith("data:"):
+            continue
+        payload = line[5:].strip()
+        if payload == "[DONE]":
+            return
+        try:
+            event = json.loads(payload)
+        except ValueError:
+            continue
+        if isinstance(event, dict) and (event.get("type") == "error" or "error" in event):
+            error = event.get("error") or {}
+            message = error.get("message") if isinstance(error, dict) else str(error)
+            raise ModelError(ModelErrorKind.UNAVAILABLE, f"流式响应中断: {message}", provider)
+        if isinstance(event, dict):
+            yield event
+
+
+def _stream_delta(style: str, event: Dict[str, Any]) -> Tuple[str, Dict[str, Optional[int]]]:
+    """单个流式事件中的文本片段与 token 用量（未包含时为空）"""
+    if style == "anthropic":
+        if event.get("type") == "content_block_delta":
+            return (event.get("delta") or {}).get("text") or "", {}
+        if event.get("type") == "message_start":
+            usage = (event.get("message") or {}).get("usage") or {}
+            return "", {"input_tokens": usage.get("input_tokens")}
+        if event.get("type") == "message_delta":
+            return "", {"output_tokens": (event.get("usage") or {}).get("output_tokens")}
+        return "", {}
+    if style == "gemini":
+        parts = (((event.get("candidates") or [{}])[0]).get("content") or {}).get("parts") or []
+        text = ''.join(part.get('text', '') for part in parts if isinstance(part, dict))
+        return text, _extract_usage(style, event) if event.get("usageMetadata") else {}
+    choices = event.get("choices") or [{}]
+    text = (choices[0].get("delta") or {}).get("content") or ""
+    return text, _extract_usage(style, event) if event.get("usage") else {}
+
+
+def chat_completion_stream(
+    settings: Dict[str, Any],
+    system_prompt: str,
+    user_content: str,
+    on_chunk: Callable[[str], None],
+    model: Optional[str] = None,
+    temperature: Optional[float] = None,
+    max_tokens: Optional[int] = None,
+    top_p: Optional[float] = None,
+    timeout: float = 30,
+    json_mode: bool = False,
+    deadline: Optional[float] = None,
+) -> str:
+    """以流式（SSE）方式发送对话请求，每收到一段文本调用一次 on_chunk，返回完整文本
+
+    支持 OpenAI 兼容接口（含 DeepSeek、Qwen）、Anthropic 与 Gemini；与 chat_completion 一样经过限流并记录到审计日志，
+    但不重试、不使用响应缓存，失败时抛出 ModelError，由调用方决定是否改用普通请求。
+    模拟提供商与录制 / 回放模式下改用 chat_completion，并把完整文本作为一个片段。
+    """
+    provider = settings["provider"]
+    model = model or settings["model"]
+    if settings["style"] == "mock" or app_config.vcr_mode != "off":
+        text = chat_completion(
+            settings, system_prompt, user_content, model=model, temperature=temperature, max_tokens=max_tokens,
+            top_p=top_p, timeout=timeout, json_mode=json_mode, deadline=deadline,
+        )
+        on_chunk(text)
+        return text
+    style = settings["style"]
+    url, headers, body = _build_chat_request(
+        settings, model, system_prompt, user_content,
+        temperature=temperature, max_tokens=max_tokens, top_p=top_p, json_mode=json_mode,
+    )
+    if style == "gemini":
+        url = url.replace(":generateContent", ":streamGenerateContent") + "?alt=sse"
+    else:
+        body["stream"] = True
+
+    started_at, started = datetime.now(), time.monotonic()
+    parts: List[str] = []
+    usage: Dict[str, Optional[int]] = {"input_tokens": None, "output_tokens": None}
+    try:
+        try:
+            acquire_provider_slot(provider, estimate_tokens(system_prompt, user_content, max_tokens=max_tokens), deadline)
+        except RateLimitTimeout as e:
+            raise ModelError(ModelErrorKind.TIMEOUT, str(e), provider) from e
+        request_timeout = timeout if deadline is None else min(timeout, max(deadline - time.monotonic(), 0.1))
+        try:
+            resp = requests.post(url, headers=headers, json=body, timeout=request_timeout, stream=True)
+        except requests.Timeout as e:
+            raise ModelError(ModelErrorKind.TIMEOUT, str(e), provider) from e
+        except requests.RequestException as e:
+            raise ModelError(ModelErrorKind.UNAVAILABLE, str(e), provider) from e
+        with resp:
+            if resp.status_code >= 400:
+                raise classify_response(provider, resp)
+            if json_mode and style == "anthropic":
+                # 与 chat_completion 一样补回预填充的 "{"
+                parts.append("{")
+                on_chunk("{")
+            try:
+                for event in _iter_sse(provider, resp):
+                    text, event_usage = _stream_delta(style, event)
+                    usage.update({key: value for key, value in event_usage.items() if value is not None})
+                    if text:
+                        parts.append(text)
+                        on_chunk(text)
+            except requests.RequestException as e:
+                raise ModelError(ModelErrorKind.UNAVAILABLE, f"流式响应中断: {e}", provider) from e
+    except ModelError as e:
+        _record_model_call(provider, model, 0, started_at, started, e, json_mode=json_mode, stream=True)
+        raise
+    _record_model_call(provider, model, 0, started_at, started, json_mode=json_mode, stream=True, **usage)
+    return ''.join(parts)
+
+
+def check_api_key_format(provider: str, key: str) -> List[str]:
+    """检查密钥的常见格式问题（空白字符、前缀错误、填错提供商）"""
+    spec = PROVIDER_SETTINGS[provider]
+    issues: List[str] = []
+    if key != key.strip():
+        issues.append("密钥首尾包含空白字符")
+    stripped = key.strip()
+    if any(ch.isspace() for ch in stripped):
+        issues.append("密钥中间包含空白字符")
+    if stripped.startswith(("Bearer ", "bearer ")):
+        issues.append("密钥不应包含 Bearer 前缀")
+    prefix = spec.get("key_prefix")
+    if prefix and not stripped.startswith(prefix):
+        issues.append(f"密钥应以 {prefix} 开头")
+    if provider != "anthropic" and stripped.startswith("sk-ant-"):
+        issues.append("这看起来是 Anthropic 的密钥")
+    return issues
+
+
+def model_metadata(model: str) -> Dict[str, Any]:
+    """按模型名返回上下文窗口与参考价格；未知模型返回空值"""
+    name = (model or "").lower().split("/")[-1]
+    for prefix, meta in MODEL_METADATA:
+        if name.startswith(prefix):
+            return dict(meta)
+    return {"context_window": None, "input_price": None, "output_price": None}
+
+
+def _fetch_model_ids(provider: str, timeout: float) -> List[Dict[str, Any]]:
+    """请求提供商的模型列表接口"""
+    if provider == "ollama":
+        base = normalize_api_base(os.getenv(OLLAMA_BASE_ENV) or OLLAMA_DEFAULT_BASE)
+        resp = requests.get(base + '/api/tags', timeout=timeout)
+        resp.raise_for_status()
+        return [{"model": m.get("name"), "context_window": None} for m in resp.json().get("models", []) if m.get("name")]
+
+    settings = get_provider_settings(provider)
+    if not settings:
+        return []
+    if settings["style"] == "mock":
+        return [{"model": MOCK_MODEL, "context_window": None}]
+    base, api_key, api_version = resolve_endpoint(settings, settings["model"])
+    if settings["style"] == "anthropic":
+        resp = requests.get(base + '/v1/models', headers={"x-api-key": api_key, "anthropic-version": "2023-06-01"}, timeout=timeout)
+    elif settings["style"] == "gemini":
+        resp = requests.get(base + '/v1beta/models', headers={"x-goog-api-key": api_key}, timeout=timeout)
+    elif is_azure_endpoint(base):
+        # Azure 的模型以部署形式提供，列表接口不反映可用部署，返回已配置的部署名
+        return [{"model": settings["model"], "context_window": None}]
+    else:
+        resp = requests.get(base + '/v1/models', headers={"Authorization": f"Bearer {api_key}"}, timeout=timeout)
+    resp.raise_for_status()
+    data = resp.json()
+
+    if settings["style"] == "gemini":
+        return [
+            {"model": m["name"].split("/")[-1], "context_window": m.get("inputTokenLimit")}
+            for m in data.get("models", [])
+            if m.get("name") and "generateContent" in (m.get("supportedGenerationMethods") or ["generateContent"])
+        ]
+    return [{"model": m.get("id"), "context_window": None} for m in data.get("data", []) if m.get("id")]
+
+
+def list_provider_models(provider: str, refresh: bool = False, timeout: float = 10) -> List[Dict[str, Any]]:
+    """查询提供商可用模型并附加上下文窗口与价格信息；结果缓存 MODEL_LIST_TTL"""
+    cache = _get_response_cache()
+    cache_key = f"models_{provider}"
+    if not refresh:
+        cached = cache.get(cache_key)
+        if cached is not None:
+            return cached
+
+    models = []
+    for entry in _fetch_model_ids(provider, timeout):
+        meta = model_metadata(entry["model"])
+        if entry.get("context_window"):
+            meta["context_window"] = entry["context_window"]
+        models.append({"provider": provider, "model": entry["model"], **meta})
+    models.sort(key=lambda m: m["model"])
+    cache.set(cache_key, models, ttl=MODEL_LIST_TTL)
+    return models
+
+
+def _models_request(provider: str, key: str, base: str) -> Tuple[str, Dict[str, str]]:
+    """模型列表接口的地址与请求头；该接口不消耗 token，用于校验密钥与检查可用性"""
+    spec = PROVIDER_SETTINGS[provider]
+    models_path = '/v1/models'
+    if spec["style"] == "anthropic":
+        headers = {"x-api-key": key, "anthropic-version": "2023-06-01"}
+    elif spec["style"] == "gemini":
+        headers = {"x-goog-api-key": key}
+        models_path = '/v1beta/models'
+    elif is_azure_endpoint(base):
+        headers = {"api-key": key}
+        base = base.split('/openai/deployments/')[0]
+        version = os.getenv(spec.get("version_env") or "") or AZURE_DEFAULT_API_VERSION
+        models_path = '/openai/models?' + urlencode({"api-version": version})
+    else:
+        headers = {"Authorization": f"Bearer {key}"}
+        if provider == "openai" and os.getenv("OPENAI_ORG_ID"):
+            headers["OpenAI-Organization"] = os.environ["OPENAI_ORG_ID"]
+    return base + models_path, headers
+
+
+def probe_provider(provider: str, timeout: float = 10) -> Dict[str, Any]:
+    """通过模型列表接口检查提供商是否可用（不产生计费请求）；与对话请求一样经过限流并记录到审计日志"""
+    settings = get_provider_settings(provider)
+    if not settings:
+        error = "离线模式下不发起请求" if app_config.offline else "未配置API密钥"
+        return {"provider": provider, "success": False, "latency_ms": None, "error": error, "error_code": None}
+
+    model = settings["model"]
+    if settings["style"] == "mock" or app_config.vcr_mode == "replay":
+        return {"provider": provider, "model": model, "success": True, "latency_ms": 0, "error": None, "error_code": None}
+    url, headers = _models_request(provider, settings["api_key"], settings["base"])
+
+    started_at, started = datetime.now(), time.monotonic()
+    error: Optional[ModelError] = None
+    try:
+        acquire_provider_slot(provider, 0, started + timeout)
+        resp = requests.get(url, headers=headers, timeout=timeout)
+        if resp.status_code >= 400:
+            error = classify_response(provider, resp)
+    except RateLimitTimeout as e:
+        error = ModelError(ModelErrorKind.TIMEOUT, str(e), provider)
+    except requests.Timeout as e:
+        error = ModelError(ModelErrorKind.TIMEOUT, str(e), provider)
+    except requests.RequestException as e:
+        error = ModelError(ModelErrorKind.UNAVAILABLE, str(e), provider)
+    _record_model_call(provider, model, 0, started_at, started, error, probe=True)
+    return {
+        "provider": provider,
+        "model": model,
+        "success": error is None,
+        "latency_ms": int((time.monotonic() - started) * 1000),
+        "error": str(error) if error else None,
+        "error_code": error.kind.value if error else None,
+    }
+
+
+def validate_api_key(provider: str, key: str, timeout: float = 10) -> Dict[str, Any]:
+    """使用提供商的模型列表接口校验密钥，返回细分的状态
+
+    status: valid | invalid | org_mismatch | forbidden | rate_limited | unreachable | error | malformed | offline
+    """
+    spec = PROVIDER_SETTINGS[provider]
+    if spec["style"] == "mock":
+        return {"provider": provider, "status": "valid", "valid": True, "latency_ms": 0,
+                "issues": [], "message": "模拟提供商不需要密钥"}
+    issues = check_api_key_format(provider, key or "")
+    key = (key or "").strip()
+    if not key:
+        return {"provider": provider, "status": "malformed", "valid": False, "latency_ms": None,
+                "issues": ["密钥为空"], "message": "密钥为空"}
+
+    if app_config.offline:
+        return {"provider": provider, "status": "offline", "valid": False, "latency_ms": None,
+                "issues": issues, "message": "离线模式下仅检查密钥格式"}
+
+    bootstrap_env_from_toml()
+    base = normalize_api_base(os.getenv(spec["base_env"]) or spec["default_base"], strip_v1=spec["style"] == "openai")
+    url, headers = _models_request(provider, key, base)
+
+    started = time.monotonic()
+    try:
+        resp = requests.get(url, headers=headers, timeout=timeout)
+    except requests.RequestException as e:
+        return {"provider": provider, "status": "unreachable", "valid": False,
+                "latency_ms": int((time.monotonic() - started) * 1000), "issues": issues, "message": str(e)}
+    latency_ms = int((time.monotonic() - started) * 1000)
+
+    if resp.status_code == 200:
+        status, message = "valid", "密钥有效"
+    elif resp.status_code == 429:
+        # 被限流说明密钥本身可用
+        status, message = "rate_limited", "密钥有效，但当前已被限流"
+    elif resp.status_code == 400 and "api_key_invalid" in resp.text.lower():
+        # Gemini 对无效密钥返回 400 API_KEY_INVALID
+        status, message = "invalid", "密钥无效或已被撤销"
+    elif resp.status_code in (401, 403) and "organization" in resp.text.lower():
+        status, message = "org_mismatch", "密钥与组织(Organization)不匹配"
+    elif resp.status_code == 401:
+        status, message = "invalid", "密钥无效或已被撤销"
+    elif resp.status_code == 403:
+        status, message = "forbidden", "密钥无权访问该接口"
+    else:
+        status, message = "error", f"HTTP {resp.status_code}: {resp.text[:200]}"
+
+    return {
+        "provider": provider,
+        "status": status,
+        "valid": status in ("valid", "rate_limited"),
+        "latency_ms": latency_ms,
+        "issues": issues,
+        "message": message,
+    }
@@ -0,0 +1,154 @@
+"""
+Provider Rate Limiting
+按提供商限制每分钟请求数与 token 数（provider_rate_limits）；超出时请求排队等待，
+不同会话之间轮流放行，避免一个会话的并行智能体占满额度
+"""
+
+import itertools
+import threading
+import time
+from collections import deque
+from typing import Deque, Dict, List, Any, Optional, Tuple
+
+from .logger import get_logger, get_log_context
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+WINDOW_SECONDS = 60.0
+
+# 每个 token 约 4 个字符（仅用于限额估算）
+CHARS_PER_TOKEN = 4
+
+
+class RateLimitTimeout(Exception):
+    """在截止时间前没有等到额度"""
+
+
+def estimate_tokens(*texts: str, max_tokens: Optional[int] = None) -> int:
+    """估算一次请求占用的 token：提示词字符数 / 4，加上输出上限"""
+    return sum(len(text or "") for text in texts) // CHARS_PER_TOKEN + (max_tokens or 0)
+
+
+class ProviderRateLimiter:
+    """单个提供商的滑动窗口限流器"""
+
+    def __init__(self, provider: str):
+        self.provider = provider
+        self._cond = threading.Condition()
+        # 窗口内已放行的请求：(放行时间, token 数)
+        self._granted: Deque[Tuple[float, int]] = deque()
+        # 等待中的请求：(排队序号, 会话)
+        self._waiting: List[Tuple[int, str]] = []
+        # 会话最近一次被放行的序号，越小越优先
+        self._last_served: Dict[str, int] = {}
+        self._seq = itertools.count()
+        self.stats = {"requests": 0, "throttled": 0, "wait_seconds": 0.0, "max_wait_seconds": 0.0}
+
+    def _limits(self) -> Tuple[int, int]:
+        limit = app_config.provider_rate_limits.get(self.provider)
+        if not limit:
+            return 0, 0
+        return limit.requests_per_minute, limit.tokens_per_minute
+
+    def _prune(self, now: float) -> None:
+        while self._granted and now - self._granted[0][0] >= WINDOW_SECONDS:
+            self._granted.popleft()
+
+    def _wait_for_capacity(self, now: float, tokens: int) -> float:
+        """距离窗口内有足够额度还需等待的秒数（0 表示可以立即放行）"""
+        rpm, tpm = self._limits()
+        wait = 0.0
+        if rpm and len(self._granted) >= rpm:
+            wait = max(wait, self._granted[len(self._granted) - rpm][0] + WINDOW_SECONDS - now)
+        if tpm:
+            # 单次估算超过每分钟上限时按上限计，保证窗口清空后总能放行
+            needed = min(tokens, tpm)
+            used = sum(t for _, t in self._granted)
+            for granted_at, granted_tokens in self._granted:
+                if used + needed <= tpm:
+                    break
+                used -= granted_tokens
+                wait = max(wait, granted_at + WINDOW_SECONDS - now)
+        return max(wait, 0.0)
+
+    def _next_waiter(self) -> Tuple[int, str]:
+        return min(self._waiting, key=lambda w: (self._last_served.get(w[1], -1), w[0]))
+
+    def acquire(self, tokens: int, session: Optional[str] = None, deadline: Optional[float] = None) -> float:
+        """等待额度并登记本次请求，返回等待的秒数；deadline（time.monotonic 时间）到期时抛出 RateLimitTimeout"""
+        session = session or ""
+        started = time.monotonic()
+        with self._cond:
+            ticket = (next(self._seq), session)
+            self._waiting.append(ticket)
+            try:
+                while True:
+                    now = time.monotonic()
+                    self._prune(now)
+                    delay = self._wait_for_capacity(now, tokens)
+                    if self._next_waiter() == ticket and delay <= 0:
+                        break
+                    if deadline is not None and now >= deadline:
+                        raise RateLimitTimeout(f"{self.provider} 等待限流额度超时")
+                    timeout = delay if delay > 0 else None
+                    if deadline is not None:
+                        timeout = min(timeout or deadline - now, deadline - now)
+                    self._cond.wait(timeout)
+            finally:
+                self._waiting.remove(ticket)
+                self._cond.notify_all()
+
+            self._granted.append((time.monotonic(), tokens))
+            self._last_served[session] = ticket[0]
+            waited = time.monotonic() - started
+            self.stats["requests"] += 1
+            if waited >= 0.01:
+                self.stats["throttled"] += 1
+                self.stats["wait_seconds"] += waited
+                self.stats["max_wait_seconds"] = max(self.stats["max_wait_seconds"], waited)
+        if waited >= 0.01:
+            logger.info(f"{self.provider} 触发限流，排队 {waited:.1f}s")
+        return waited
+
+    def snapshot(self) -> Dict[str, Any]:
+        with self._cond:
+            self._prune(time.monotonic())
+            rpm, tpm = self._limits()
+            return {
+                "provider": self.provider,
+                "requests_per_minute": rpm,
+                "tokens_per_minute": tpm,
+                "window_requests": len(self._granted),
+                "window_tokens": sum(t for _, t in self._granted),
+                "waiting": len(self._waiting),
+                **self.stats,
+                "wait_seconds": round(self.stats["wait_seconds"], 3),
+                "max_wait_seconds": round(self.stats["max_wait_seconds"], 3),
+            }
+
+
+_limiters: Dict[str, ProviderRateLimiter] = {}
+_limiters_lock = threading.Lock()
+
+
+def get_rate_limiter(provider: str) -> ProviderRateLimiter:
+    with _limiters_lock:
+        if provider not in _limiters:
+            _limiters[provider] = ProviderRateLimiter(provider)
+        return _limiters[provider]
+
+
+def acquire_provider_slot(provider: str, tokens: int, deadline: Optional[float] = None) -> float:
+    """未配置限额的提供商直接放行；会话取自 log_context 中的 session_id"""
+    limit = app_config.provider_rate_limits.get(provider)
+    if not limit or not (limit.requests_per_minute or limit.tokens_per_minute):
+        return 0.0
+    session = get_log_context().get("session_id")
+    return get_rate_limiter(provider).acquire(tokens, session, deadline)
+
+
+def rate_limit_stats() -> List[Dict[str, Any]]:
+    """已配置限额或已产生请求的提供商的窗口用量与排队统计"""
+    providers = dict.fromkeys([*app_config.provider_rate_limits, *_limiters])
+    return [get_rate_limiter(provider).snapshot() for provider in providers]
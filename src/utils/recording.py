@@ -0,0 +1,53 @@
+"""
+Model Request Recording
+录制真实的模型请求与响应到夹具文件（vcr_mode=record），并在回放模式（vcr_mode=replay）下直接返回录制的响应，
+用于在没有密钥的环境中确定性地运行完整的澄清与多智能体流程
+"""
+
+import hashlib
+import json
+from pathlib import Path
+from typing import Dict, Any, Optional
+from urllib.parse import urlsplit
+
+from .logger import get_logger
+from .safety import confidential_terms, redact_data
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+
+def recording_key(provider: str, url: str, body: Dict[str, Any]) -> str:
+    """按提供商、接口路径与请求体计算键；不包含主机地址与查询参数，录制与回放可以使用不同的网关"""
+    payload = json.dumps({"provider": provider, "path": urlsplit(url).path, "body": body}, sort_keys=True, ensure_ascii=False)
+    return hashlib.sha256(payload.encode("utf-8")).hexdigest()[:24]
+
+
+def recording_path(provider: str, url: str, body: Dict[str, Any]) -> Path:
+    return app_config.vcr_dir / f"{provider}-{recording_key(provider, url, body)}.json"
+
+
+def load_recording(provider: str, url: str, body: Dict[str, Any]) -> Optional[Dict[str, Any]]:
+    path = recording_path(provider, url, body)
+    if not path.exists():
+        return None
+    with open(path, 'r', encoding='utf-8') as f:
+        recording = json.load(f)
+    logger.debug(f"回放录制的响应: {path.name}")
+    return recording["response"]
+
+
+def save_recording(provider: str, url: str, body: Dict[str, Any], response: Dict[str, Any]) -> Path:
+    """写入夹具文件；请求体仅供阅读，其中的密钥、邮箱与保密词会被遮盖"""
+    path = recording_path(provider, url, body)
+    path.parent.mkdir(parents=True, exist_ok=True)
+    recording = {
+        "provider": provider,
+        "path": urlsplit(url).path,
+        "request": redact_data(body, confidential_terms()),
+        "response": response,
+    }
+    with open(path, 'w', encoding='utf-8') as f:
+        json.dump(recording, f, ensure_ascii=False, indent=2)
+    logger.debug(f"已录制响应: {path.name}")
+    return path
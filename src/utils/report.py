@@ -0,0 +1,247 @@
+"""
+Report Rendering Utilities
+将澄清会话（原始想法、问答、总结）渲染为 Markdown / JSON / HTML / PDF
+"""
+
+import html
+import json
+import os
+import shutil
+import subprocess
+import sys
+import tempfile
+from datetime import datetime
+from pathlib import Path
+from typing import Dict, List, Any, Optional
+
+from .i18n import DEFAULT_LANGUAGE, output_language, t
+from .safety import confidential_terms, redact_data
+from .templates import domain_field_labels
+
+# 总结字段（顺序即报告中的顺序），展示名称见 config/locales 中的 section.<字段>
+SUMMARY_SECTIONS = [
+    "refined_idea",
+    "user_segments",
+    "core_pain_points",
+    "key_features",
+    "constraints",
+    "success_metrics",
+    "risks",
+    "next_steps",
+]
+
+EXPORT_FORMATS = {
+    "markdown": "md",
+    "json": "json",
+    "html": "html",
+    "pdf": "pdf",
+}
+
+# 用于 HTML -> PDF 的无头浏览器（与 scripts/md2pdf.js 一样使用系统自带的 Chromium 内核浏览器）
+BROWSER_CANDIDATES = {
+    "win32": [
+        r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+        r"C:\Program Files\Microsoft\Edge\Application\msedge.exe",
+        r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+        r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+    ],
+    "darwin": [
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    ],
+}
+BROWSER_COMMANDS = ["msedge", "microsoft-edge", "google-chrome", "google-chrome-stable", "chromium", "chromium-browser", "chrome"]
+
+
+def build_report(session: Dict[str, Any]) -> Dict[str, Any]:
+    """从会话数据整理出与格式无关的报告结构（标签使用会话的输出语言）"""
+    seed = session.get("idea_seed") or {}
+    summary = session.get("summary") or {}
+    language = output_language(seed.get("language")) or DEFAULT_LANGUAGE
+    title = summary.get("title") or session.get("title") or (seed.get("raw_text") or "")[:30] or session.get("id", "")
+
+    # 通用总结字段之后追加领域模板的专属字段（domain_fields）
+    labels = domain_field_labels(seed.get("domain"), language)
+    fields = [(key, t(f"section.{key}", language), summary.get(key)) for key in SUMMARY_SECTIONS]
+    fields += [
+        (f"domain_fields.{key}", labels.get(key, key), value)
+        for key, value in (summary.get("domain_fields") or {}).items()
+    ]
+
+    sections = []
+    for key, label, value in fields:
+        if not value:
+            continue
+        items = [str(x) for x in value] if isinstance(value, list) else None
+        sections.append({
+            "key": key,
+            "title": label,
+            "text": None if items is not None else str(value),
+            "items": items,
+        })
+
+    qa_pairs = [
+        {"question": q.get("question", ""), "answer": q.get("answer")}
+        for q in session.get("questions", [])
+        if q.get("answer")
+    ]
+
+    report = {
+        "title": title,
+        "language": language,
+        "session_id": session.get("id"),
+        "domain": seed.get("domain"),
+        "original_idea": seed.get("raw_text", ""),
+        "qa_pairs": qa_pairs,
+        "sections": sections,
+        "created_at": session.get("created_at"),
+        "exported_at": datetime.now().isoformat(),
+    }
+    # 导出内容遮盖密钥、邮箱与该会话的保密词
+    return redact_data(report, confidential_terms(session.get("id")))
+
+
+def _field(label_key: str, value: str, language: Optional[str]) -> str:
+    """"标签：值" 形式的元信息行，分隔符随语言变化"""
+    return t("report.field", language, label=t(label_key, language), value=value)
+
+
+def render_markdown(report: Dict[str, Any]) -> str:
+    lang = report.get("language")
+    lines: List[str] = [f"# {report['title']}", ""]
+    lines.append("- " + _field("report.session", f"`{report['session_id']}`", lang))
+    if report.get("domain"):
+        lines.append("- " + _field("report.domain", report["domain"], lang))
+    lines.append("- " + _field("report.exported_at", report["exported_at"], lang))
+    lines += ["", f"## {t('report.original_idea', lang)}", "", report["original_idea"] or t("report.empty", lang), ""]
+
+    if report["qa_pairs"]:
+        lines += [f"## {t('report.qa', lang)}", ""]
+        for qa in report["qa_pairs"]:
+            lines.append(f"- **{qa['question']}**")
+            lines.append(f"  {qa['answer']}")
+        lines.append("")
+
+    for section in report["sections"]:
+        lines += [f"## {section['title']}", ""]
+        if section["items"] is not None:
+            lines += [f"- {item}" for item in section["items"]]
+        else:
+            lines.append(section["text"])
+        lines.append("")
+
+    return "\n".join(lines)
+
+
+def render_json(report: Dict[str, Any]) -> str:
+    return json.dumps(report, ensure_ascii=False, indent=2)
+
+
+def render_html(report: Dict[str, Any], title_page: bool = False) -> str:
+    esc = html.escape
+    lang = report.get("language")
+    body: List[str] = []
+    if title_page:
+        body.append(
+            '<section class="title-page">'
+            f"<h1>{esc(report['title'])}</h1>"
+            f"<p>{esc(report.get('domain') or '')}</p>"
+            f"<p>{_field('report.created_at', esc(report.get('created_at') or ''), lang)}</p>"
+            f"<p>{_field('report.exported_at', esc(report['exported_at']), lang)}</p>"
+            "</section>"
+        )
+    body.append(f"<h1>{esc(report['title'])}</h1>")
+    meta = [_field("report.session", f"<code>{esc(report['session_id'] or '')}</code>", lang)]
+    if report.get("domain"):
+        meta.append(_field("report.domain", esc(report["domain"]), lang))
+    meta.append(_field("report.exported_at", esc(report["exported_at"]), lang))
+    body.append('<p class="meta">' + " · ".join(meta) + "</p>")
+    body.append(f"<h2>{esc(t('report.original_idea', lang))}</h2><p>{esc(report['original_idea'] or t('report.empty', lang))}</p>")
+
+    if report["qa_pairs"]:
+        body.append(f"<h2>{esc(t('report.qa', lang))}</h2><dl>")
+        for qa in report["qa_pairs"]:
+            body.append(f"<dt>{esc(qa['question'])}</dt><dd>{esc(qa['answer'])}</dd>")
+        body.append("</dl>")
+
+    for section in report["sections"]:
+        body.append(f"<h2>{esc(section['title'])}</h2>")
+        if section["items"] is not None:
+            body.append("<ul>" + "".join(f"<li>{esc(item)}</li>" for item in section["items"]) + "</ul>")
+        else:
+            body.append(f"<p>{esc(section['text'])}</p>")
+
+    return f"""<!DOCTYPE html>
+<html lang="{esc(lang or DEFAULT_LANGUAGE)}">
+<head>
+<meta charset="utf-8">
+<title>{esc(report['title'])}</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", "Microsoft YaHei", sans-serif; max-width: 860px; margin: 40px auto; padding: 0 20px; color: #1f2937; line-height: 1.7; }}
+h1 {{ border-bottom: 2px solid #3b82f6; padding-bottom: 8px; }}
+h2 {{ margin-top: 32px; color: #1e40af; }}
+.meta {{ color: #6b7280; font-size: 14px; }}
+dt {{ font-weight: 600; margin-top: 12px; }}
+dd {{ margin-left: 16px; }}
+.title-page {{ height: 90vh; display: flex; flex-direction: column; justify-content: center; text-align: center; page-break-after: always; }}
+.title-page h1 {{ border: none; font-size: 36px; }}
+@media print {{ body {{ margin: 0 auto; }} h2 {{ page-break-after: avoid; }} }}
+</style>
+</head>
+<body>
+{chr(10).join(body)}
+</body>
+</html>
+"""
+
+
+def find_headless_browser() -> Optional[str]:
+    """查找可用于打印 PDF 的 Chromium 内核浏览器"""
+    for candidate in BROWSER_CANDIDATES.get(sys.platform, []):
+        if os.path.exists(candidate):
+            return candidate
+    for command in BROWSER_COMMANDS:
+        found = shutil.which(command)
+        if found:
+            return found
+    return None
+
+
+def write_pdf(report: Dict[str, Any], pdf_path: Path, timeout: int = 60) -> None:
+    """渲染带封面的 HTML，并通过无头浏览器打印为 PDF"""
+    browser = find_headless_browser()
+    if not browser:
+        raise RuntimeError("未找到 Edge/Chrome/Chromium，无法导出 PDF")
+
+    with tempfile.TemporaryDirectory(prefix="magent_pdf_") as tmp_dir:
+        html_path = Path(tmp_dir) / "report.html"
+        html_path.write_text(render_html(report, title_page=True), encoding="utf-8")
+        subprocess.run(
+            [
+                browser,
+                "--headless=new",
+                "--disable-gpu",
+                "--no-pdf-header-footer",
+                f"--user-data-dir={Path(tmp_dir) / 'profile'}",
+                f"--print-to-pdf={pdf_path.resolve()}",
+                html_path.resolve().as_uri(),
+            ],
+            check=True,
+            timeout=timeout,
+            stdout=subprocess.DEVNULL,
+            stderr=subprocess.DEVNULL,
+        )
+    if not pdf_path.exists():
+        raise RuntimeError("浏览器未生成 PDF 文件")
+
+
+def render_report(report: Dict[str, Any], fmt: str) -> str:
+    renderers = {
+        "markdown": render_markdown,
+        "json": render_json,
+        "html": render_html,
+    }
+    if fmt not in renderers:
+        raise ValueError(f"不支持的导出格式: {fmt}")
+    return renderers[fmt](report)
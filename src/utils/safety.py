@@ -0,0 +1,188 @@
+"""
+Content Safety Utilities
+在想法文本与上下文写入提示词之前做清理：移除试图覆盖系统提示词的指令、遮盖误粘贴的密钥，并可选地调用内容审核接口；
+以及日志、响应缓存与导出内容中的密钥、邮箱与会话保密词遮盖
+"""
+
+import re
+import threading
+from dataclasses import dataclass, field, asdict
+from typing import Dict, List, Any, Optional, Tuple
+
+from .logger import get_logger
+from config.app_config import app_config
+
+logger = get_logger(__name__)
+
+# 常见密钥格式（OpenAI / Anthropic / Google / AWS / GitHub / Slack / 私钥块 / Bearer 令牌）
+SECRET_PATTERNS: List[Tuple[str, re.Pattern]] = [
+    ("private_key", re.compile(r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----")),
+    ("anthropic_key", re.compile(r"\bsk-ant-[A-Za-z0-9_\-]{20,}")),
+    ("openai_key", re.compile(r"\bsk-(?:proj-)?[A-Za-z0-9_\-]{20,}")),
+    ("google_key", re.compile(r"\bAIza[0-9A-Za-z_\-]{35}\b")),
+    ("aws_access_key", re.compile(r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b")),
+    ("github_token", re.compile(r"\bgh[pousr]_[A-Za-z0-9]{36,}\b")),
+    ("slack_token", re.compile(r"\bxox[abpr]-[A-Za-z0-9\-]{10,}")),
+    ("bearer_token", re.compile(r"(?i)\bbearer\s+[A-Za-z0-9_\-\.=]{20,}")),
+]
+
+# 试图改写系统提示词或伪造对话角色的内容
+INJECTION_PATTERNS: List[Tuple[str, re.Pattern]] = [
+    ("ignore_instructions", re.compile(
+        r"(?i)\b(?:ignore|disregard|forget|override)\s+(?:all\s+|any\s+)?(?:the\s+|your\s+)?"
+        r"(?:previous|prior|above|earlier|system)\s+(?:instructions?|prompts?|rules|messages)"
+    )),
+    ("ignore_instructions", re.compile(r"(?:忽略|无视|忘记|忘掉)(?:掉)?(?:你)?(?:之前|以上|前面|上面|所有|系统)的?(?:所有)?(?:指令|指示|提示词?|规则|设定)")),
+    ("role_override", re.compile(r"(?i)\b(?:you are now|from now on you are|act as the system|new system prompt)\b")),
+    ("role_override", re.compile(r"(?:你现在是|从现在开始你是|新的系统提示)")),
+    ("role_marker", re.compile(r"(?im)^\s*(?:system|assistant)\s*:")),
+    ("role_marker", re.compile(r"(?i)<\|(?:im_start|im_end|system|endoftext)\|>|\[/?INST\]|<<\s*/?SYS\s*>>|</?system>")),
+]
+
+REMOVED_MARK = "[removed]"
+
+EMAIL_PATTERN = re.compile(r"\b[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}\b")
+CONFIDENTIAL_MARK = "[confidential]"
+
+# 会话 ID -> 保密词；首次使用时从已保存的会话中加载
+_confidential_terms: Optional[Dict[str, List[str]]] = None
+_terms_lock = threading.RLock()
+
+
+@dataclass
+class SanitizeResult:
+    text: str
+    secrets: Dict[str, int] = field(default_factory=dict)
+    injections: Dict[str, int] = field(default_factory=dict)
+
+    @property
+    def changed(self) -> bool:
+        return bool(self.secrets or self.injections)
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data.pop("text")
+        return data
+
+
+def redact_secrets(text: str) -> Tuple[str, Dict[str, int]]:
+    """把密钥替换为 [redacted:<类型>]，返回替换后的文本与各类型的次数"""
+    counts: Dict[str, int] = {}
+    for kind, pattern in SECRET_PATTERNS:
+        text, n = pattern.subn(f"[redacted:{kind}]", text)
+        if n:
+            counts[kind] = counts.get(kind, 0) + n
+    return text, counts
+
+
+def strip_injections(text: str) -> Tuple[str, Dict[str, int]]:
+    counts: Dict[str, int] = {}
+    for kind, pattern in INJECTION_PATTERNS:
+        text, n = pattern.subn(REMOVED_MARK, text)
+        if n:
+            counts[kind] = counts.get(kind, 0) + n
+    return text, counts
+
+
+def sanitize_prompt_text(text: Optional[str]) -> SanitizeResult:
+    """清理将写入提示词的用户文本；safety_filter_enabled 关闭时原样返回"""
+    text = text or ""
+    if not app_config.safety_filter_enabled:
+        return SanitizeResult(text)
+    text, secrets = redact_secrets(text)
+    text, injections = strip_injections(text)
+    return SanitizeResult(text, secrets, injections)
+
+
+def moderate_text(text: str, timeout: float = 10) -> Optional[Dict[str, Any]]:
+    """调用 OpenAI 内容审核接口；未开启 moderation_enabled、离线或未配置 OpenAI 密钥时返回 None"""
+    if not app_config.moderation_enabled:
+        return None
+    from .providers import ModelError, get_provider_settings, is_azure_endpoint, _post_once
+
+    settings = get_provider_settings("openai")
+    if not settings or is_azure_endpoint(settings["base"]):
+        logger.info("内容审核已开启，但没有可用的 OpenAI 接口，跳过审核")
+        return None
+    try:
+        data = _post_once(
+            "openai",
+            settings["base"] + "/v1/moderations",
+            {"Authorization": f"Bearer {settings['api_key']}", "Content-Type": "application/json"},
+            {"model": "omni-moderation-latest", "input": text},
+            timeout,
+        )
+    except ModelError as e:
+        logger.warning(f"内容审核请求失败: {e}")
+        return {"provider": "openai", "error": str(e)}
+    result = (data.get("results") or [{}])[0]
+    return {
+        "provider": "openai",
+        "flagged": bool(result.get("flagged")),
+        "categories": sorted(name for name, hit in (result.get("categories") or {}).items() if hit),
+    }
+
+
+def _ensure_terms_loaded() -> Dict[str, List[str]]:
+    global _confidential_terms
+    with _terms_lock:
+        if _confidential_terms is None:
+            # 先置空，避免加载过程中的日志再次触发加载
+            _confidential_terms = {}
+            from .storage import SessionStorage
+            for session in SessionStorage().list_sessions():
+                if session.get("id") and session.get("confidential_terms"):
+                    _confidential_terms[session["id"]] = list(session["confidential_terms"])
+        return _confidential_terms
+
+
+def set_confidential_terms(session_id: str, terms: List[str]) -> None:
+    with _terms_lock:
+        registry = _ensure_terms_loaded()
+        if terms:
+            registry[session_id] = list(terms)
+        else:
+            registry.pop(session_id, None)
+
+
+def confidential_terms(session_id: Optional[str] = None) -> List[str]:
+    """指定会话的保密词；不指定时返回所有会话的保密词（用于日志与缓存这类不区分会话的输出）"""
+    with _terms_lock:
+        registry = _ensure_terms_loaded()
+        if session_id is not None:
+            return list(registry.get(session_id, []))
+        return sorted({term for terms in registry.values() for term in terms})
+
+
+def redact_text(text: str, terms: Optional[List[str]] = None) -> str:
+    """遮盖密钥、邮箱地址与保密词（保密词不区分大小写）；redact_sensitive_data 关闭时原样返回"""
+    if not text or not app_config.redact_sensitive_data:
+        return text
+    text, _ = redact_secrets(text)
+    text = EMAIL_PATTERN.sub("[redacted:email]", text)
+    for term in sorted(terms or [], key=len, reverse=True):
+        text = re.sub(re.escape(term), CONFIDENTIAL_MARK, text, flags=re.IGNORECASE)
+    return text
+
+
+def redact_data(data: Any, terms: Optional[List[str]] = None) -> Any:
+    """递归遮盖 dict / list 中的字符串值"""
+    if isinstance(data, str):
+        return redact_text(data, terms)
+    if isinstance(data, dict):
+        return {key: redact_data(value, terms) for key, value in data.items()}
+    if isinstance(data, list):
+        return [redact_data(value, terms) for value in data]
+    return data
+
+
+def check_idea(text: str) -> Dict[str, Any]:
+    """会话创建时的安全检查结果（记录在会话的 safety 字段中）"""
+    sanitized = sanitize_prompt_text(text)
+    report: Dict[str, Any] = sanitized.to_dict()
+    moderation = moderate_text(sanitized.text)
+    if moderation is not None:
+        report["moderation"] = moderation
+    if sanitized.changed:
+        logger.warning(f"想法文本包含需清理的内容: secrets={sanitized.secrets} injections={sanitized.injections}")
+    return report
@@ -0,0 +1,107 @@
+"""
+Session Search
+在已保存的澄清会话中按关键词检索（想法原文、上下文提示、澄清回答、各版本总结），返回按相关度排序的结果与摘录
+"""
+
+import re
+from typing import Dict, List, Any, Iterable, Optional, Tuple
+
+from .storage import SessionStorage
+
+# 字段权重：标题与想法原文的命中比总结历史中的命中更相关
+FIELD_WEIGHTS: Dict[str, float] = {
+    "title": 3.0,
+    "idea": 2.5,
+    "summary": 2.0,
+    "answer": 1.5,
+    "context": 1.0,
+    "summary_history": 0.5,
+}
+
+SNIPPET_RADIUS = 40
+
+
+def parse_query(query: str) -> List[str]:
+    """拆分查询词：空白分隔，双引号内作为整体短语；不区分大小写"""
+    terms = []
+    for phrase, word in re.findall(r'"([^"]*)"|([^\s"]+)', query or ""):
+        term = (phrase or word).strip().lower()
+        if term and term not in terms:
+            terms.append(term)
+    return terms
+
+
+def _flatten(value: Any) -> Iterable[str]:
+    if isinstance(value, str):
+        yield value
+    elif isinstance(value, dict):
+        for item in value.values():
+            yield from _flatten(item)
+    elif isinstance(value, list):
+        for item in value:
+            yield from _flatten(item)
+
+
+def _session_fields(session: Dict[str, Any]) -> List[Tuple[str, str]]:
+    seed = session.get("idea_seed") or {}
+    fields = [
+        ("title", session.get("title") or ""),
+        ("idea", seed.get("raw_text") or ""),
+        ("context", "\n".join(_flatten(seed.get("context_hints") or []))),
+        ("summary", "\n".join(_flatten(session.get("summary") or {}))),
+    ]
+    for q in session.get("questions", []):
+        if q.get("answer"):
+            fields.append(("answer", f"{q.get('question', '')}\n{q['answer']}"))
+    # 当前总结之外的历史版本
+    for entry in (session.get("summary_history") or [])[:-1]:
+        fields.append(("summary_history", "\n".join(_flatten(entry.get("summary") or {}))))
+    return [(name, text) for name, text in fields if text]
+
+
+def _snippet(text: str, start: int, end: int) -> str:
+    left = max(0, start - SNIPPET_RADIUS)
+    right = min(len(text), end + SNIPPET_RADIUS)
+    snippet = re.sub(r"\s+", " ", text[left:right]).strip()
+    return f"{'…' if left else ''}{snippet}{'…' if right < len(text) else ''}"
+
+
+def score_session(session: Dict[str, Any], terms: List[str]) -> Optional[Dict[str, Any]]:
+    """所有查询词都出现时返回得分与命中摘录，否则返回 None"""
+    score = 0.0
+    matched = set()
+    snippets: List[Dict[str, str]] = []
+    for name, text in _session_fields(session):
+        lowered = text.lower()
+        field_hit = None
+        for term in terms:
+            count = lowered.count(term)
+            if not count:
+                continue
+            matched.add(term)
+            # 同一字段中的重复命中递减计分
+            score += FIELD_WEIGHTS[name] * (1 + 0.25 * (min(count, 9) - 1))
+            if field_hit is None:
+                pos = lowered.index(term)
+                field_hit = (pos, pos + len(term))
+        if field_hit is not None:
+            snippets.append({"field": name, "text": _snippet(text, *field_hit)})
+    if len(matched) < len(terms):
+        return None
+    return {"score": round(score, 2), "snippets": snippets[:3]}
+
+
+def search_sessions(query: str, limit: int = 20) -> List[Tuple[Dict[str, Any], Dict[str, Any]]]:
+    """返回 (会话, 得分与摘录) 列表，按得分降序，得分相同时较新的会话在前"""
+    terms = parse_query(query)
+    if not terms:
+        return []
+    hits = []
+    for session in SessionStorage().list_sessions():
+        if not session.get("id"):
+            continue
+        result = score_session(session, terms)
+        if result:
+            hits.append((session, result))
+    hits.sort(key=lambda hit: (hit[1]["score"], hit[0].get("updated_at") or ""), reverse=True)
+    return hits[:max(1, limit)]
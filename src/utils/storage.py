@@ -4,6 +4,9 @@ Storage Utilities
 
 import json
 import os
+import re
+import threading
+import time
 from pathlib import Path
 from typing import Dict, List, Optional, Any
 from datetime import datetime, timedelta
@@ -15,6 +18,26 @@ from config.app_config import app_config
 logger = get_logger(__name__)
 
 
+# 用作文件名的会话与项目 ID：字母或数字开头，仅含字母、数字、下划线与连字符（不能包含路径分隔符或 ..）
+STORAGE_ID_PATTERN = re.compile(r"^[A-Za-z0-9][A-Za-z0-9_-]{0,127}$")
+
+
+def is_valid_storage_id(value: Any) -> bool:
+    return isinstance(value, str) and bool(STORAGE_ID_PATTERN.match(value))
+
+
+def storage_file(directory: Path, item_id: Any) -> Path:
+    """ID 对应的 JSON 文件路径；ID 不合法时抛出 ValueError，避免拼接出目录之外的路径"""
+    if not is_valid_storage_id(item_id):
+        raise ValueError(f"无效的 ID: {item_id!r}")
+    return directory / f"{item_id}.json"
+
+
+def new_session_id() -> str:
+    """新的澄清会话 ID，例如 clar_20240101_120000_000000"""
+    return f"clar_{datetime.now().strftime('%Y%m%d_%H%M%S_%f')}"
+
+
 class DateTimeEncoder(json.JSONEncoder):
     """自定义JSON编码器，处理datetime对象"""
     def default(self, obj):
@@ -33,7 +56,7 @@ class ProjectStorage:
     def save_project(self, project: Project) -> bool:
         """保存项目"""
         try:
-            project_file = self.projects_dir / f"{project.id}.json"
+            project_file = storage_file(self.projects_dir, project.id)
 
             with open(project_file, 'w', encoding='utf-8') as f:
                 json.dump(project.to_dict(), f, ensure_ascii=False, indent=2, cls=DateTimeEncoder)
@@ -48,7 +71,7 @@ class ProjectStorage:
     def load_project(self, project_id: str) -> Optional[Project]:
         """加载项目"""
         try:
-            project_file = self.projects_dir / f"{project_id}.json"
+            project_file = storage_file(self.projects_dir, project_id)
             
             if not project_file.exists():
                 return None
@@ -91,7 +114,7 @@ class ProjectStorage:
     def delete_project(self, project_id: str) -> bool:
         """删除项目"""
         try:
-            project_file = self.projects_dir / f"{project_id}.json"
+            project_file = storage_file(self.projects_dir, project_id)
             
             if project_file.exists():
                 project_file.unlink()
@@ -116,7 +139,7 @@ class SessionStorage:
     def save_session(self, session_id: str, session_data: Dict[str, Any]) -> bool:
         """保存会话"""
         try:
-            session_file = self.sessions_dir / f"{session_id}.json"
+            session_file = storage_file(self.sessions_dir, session_id)
             
             with open(session_file, 'w', encoding='utf-8') as f:
                 json.dump(session_data, f, ensure_ascii=False, indent=2)
@@ -131,7 +154,7 @@ class SessionStorage:
     def load_session(self, session_id: str) -> Optional[Dict[str, Any]]:
         """加载会话"""
         try:
-            session_file = self.sessions_dir / f"{session_id}.json"
+            session_file = storage_file(self.sessions_dir, session_id)
             
             if not session_file.exists():
                 return None
@@ -145,16 +168,79 @@ class SessionStorage:
         except Exception as e:
             logger.error(f"加载会话失败: {e}")
             return None
+    
+    def list_sessions(self) -> List[Dict[str, Any]]:
+        """列出所有会话"""
+        try:
+            sessions = []
+            
+            for session_file in self.sessions_dir.glob("*.json"):
+                try:
+                    with open(session_file, 'r', encoding='utf-8') as f:
+                        sessions.append(json.load(f))
+                except Exception as e:
+                    logger.warning(f"跳过损坏的会话文件 {session_file}: {e}")
+                    continue
+            
+            logger.debug(f"列出会话: 共 {len(sessions)} 个")
+            return sessions
+            
+        except Exception as e:
+            logger.error(f"列出会话失败: {e}")
+            return []
+    
+    def delete_session(self, session_id: str) -> bool:
+        """删除会话"""
+        try:
+            session_file = storage_file(self.sessions_dir, session_id)
+            
+            if session_file.exists():
+                session_file.unlink()
+                logger.debug(f"会话已删除: {session_file}")
+                return True
+            else:
+                logger.warning(f"会话文件不存在: {session_file}")
+                return False
+                
+        except Exception as e:
+            logger.error(f"删除会话失败: {e}")
+            return False
 
 
 class CacheStorage:
     """缓存存储管理"""
+
+    # 进程内的命中统计（所有实例共享）
+    _stats = {"hits": 0, "misses": 0, "sets": 0, "expired": 0, "evicted": 0}
+    # 各缓存目录的占用估计：写入时累加，只有超过上限时才扫描目录；删除不扣减，下次扫描时校正
+    _usage: Dict[str, int] = {}
+    _lock = threading.Lock()
     
     def __init__(self):
         self.cache_dir = app_config.cache_dir
         self.cache_dir.mkdir(parents=True, exist_ok=True)
         self.default_ttl = timedelta(hours=24)
     
+    def _count(self, name: str) -> None:
+        with CacheStorage._lock:
+            CacheStorage._stats[name] += 1
+    
+    def _max_bytes(self) -> int:
+        return int(app_config.cache_max_size_mb * 1024 * 1024)
+    
+    def _track_usage(self, delta: int) -> None:
+        """累加写入量，估计值未知或超过上限时才执行容量限制"""
+        max_bytes = self._max_bytes()
+        if max_bytes <= 0:
+            return
+        with CacheStorage._lock:
+            usage = CacheStorage._usage.get(str(self.cache_dir))
+            if usage is not None:
+                usage += delta
+                CacheStorage._usage[str(self.cache_dir)] = usage
+        if usage is None or usage > max_bytes:
+            self.enforce_size_limit(max_bytes)
+    
     def set(self, key: str, value: Any, ttl: Optional[timedelta] = None) -> bool:
         """设置缓存"""
         try:
@@ -167,10 +253,13 @@ class CacheStorage:
             }
             
             cache_file = self.cache_dir / f"{key}.json"
+            previous_size = cache_file.stat().st_size if cache_file.exists() else 0
             
             with open(cache_file, 'w', encoding='utf-8') as f:
                 json.dump(cache_data, f, ensure_ascii=False, indent=2)
             
+            self._count("sets")
+            self._track_usage(cache_file.stat().st_size - previous_size)
             logger.debug(f"缓存已设置: {key}")
             return True
             
@@ -184,6 +273,7 @@ class CacheStorage:
             cache_file = self.cache_dir / f"{key}.json"
             
             if not cache_file.exists():
+                self._count("misses")
                 return None
             
             with open(cache_file, 'r', encoding='utf-8') as f:
@@ -194,9 +284,14 @@ class CacheStorage:
             if datetime.now() > expires_at:
                 # 删除过期缓存
                 cache_file.unlink()
+                self._count("expired")
+                self._count("misses")
                 logger.debug(f"缓存已过期并删除: {key}")
                 return None
             
+            # 更新修改时间，超出容量时按最久未使用淘汰
+            os.utime(cache_file)
+            self._count("hits")
             logger.debug(f"缓存命中: {key}")
             return cache_data["value"]
             
@@ -234,6 +329,7 @@ class CacheStorage:
                     if datetime.now() > expires_at:
                         cache_file.unlink()
                         cleared_count += 1
+                        self._count("expired")
                         
                 except Exception as e:
                     logger.warning(f"清理缓存文件失败 {cache_file}: {e}")
@@ -245,3 +341,89 @@ class CacheStorage:
         except Exception as e:
             logger.error(f"清理过期缓存失败: {e}")
             return 0
+    
+    def enforce_size_limit(self, max_bytes: Optional[int] = None) -> int:
+        """缓存总大小超过上限时删除最久未使用的文件，返回删除数量"""
+        if max_bytes is None:
+            max_bytes = self._max_bytes()
+        if max_bytes <= 0:
+            return 0
+        try:
+            files = []
+            for cache_file in self.cache_dir.glob("*.json"):
+                try:
+                    stat = cache_file.stat()
+                except FileNotFoundError:
+                    continue
+                files.append((stat.st_mtime, stat.st_size, cache_file))
+            total = sum(size for _, size, _ in files)
+            evicted = 0
+            for _, size, cache_file in sorted(files, key=lambda x: x[0]):
+                if total <= max_bytes:
+                    break
+                try:
+                    cache_file.unlink()
+                except FileNotFoundError:
+                    pass
+                total -= size
+                evicted += 1
+                self._count("evicted")
+            with CacheStorage._lock:
+                CacheStorage._usage[str(self.cache_dir)] = total
+            if evicted:
+                logger.info(f"缓存超出容量上限，已淘汰 {evicted} 个文件")
+            return evicted
+            
+        except Exception as e:
+            logger.error(f"清理超限缓存失败: {e}")
+            return 0
+    
+    def clear(self) -> int:
+        """清空全部缓存"""
+        cleared_count = 0
+        for cache_file in self.cache_dir.glob("*.json"):
+            try:
+                cache_file.unlink()
+                cleared_count += 1
+            except FileNotFoundError:
+                continue
+        with CacheStorage._lock:
+            CacheStorage._usage[str(self.cache_dir)] = 0
+        logger.info(f"已清空缓存: {cleared_count} 个文件")
+        return cleared_count
+    
+    def stats(self) -> Dict[str, Any]:
+        """缓存条目数、占用空间与命中统计"""
+        sizes = [f.stat().st_size for f in self.cache_dir.glob("*.json") if f.exists()]
+        with CacheStorage._lock:
+            counters = dict(CacheStorage._stats)
+        lookups = counters["hits"] + counters["misses"]
+        return {
+            "cache_dir": str(self.cache_dir),
+            "entries": len(sizes),
+            "total_bytes": sum(sizes),
+            "max_bytes": self._max_bytes(),
+            "hit_rate": round(counters["hits"] / lookups, 4) if lookups else None,
+            **counters,
+        }
+
+
+_janitor_thread: Optional[threading.Thread] = None
+
+
+def start_cache_janitor(interval_seconds: Optional[float] = None) -> None:
+    """启动后台线程，定期清理过期缓存并执行容量限制"""
+    global _janitor_thread
+    if _janitor_thread and _janitor_thread.is_alive():
+        return
+    interval = interval_seconds or app_config.cache_janitor_interval
+
+    def _run():
+        cache = CacheStorage()
+        while True:
+            cache.clear_expired()
+            cache.enforce_size_limit()
+            time.sleep(interval)
+
+    _janitor_thread = threading.Thread(target=_run, name="cache-janitor", daemon=True)
+    _janitor_thread.start()
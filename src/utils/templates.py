@@ -0,0 +1,123 @@
+"""
+Domain Templates
+按领域预置的澄清槽位与问题库，以及总结中的领域专属字段；通过想法种子的 domain 选择
+"""
+
+from typing import Dict, List, Any, Optional
+
+from .i18n import SUPPORTED_LANGUAGES, t
+
+
+# 文案（展示名、问题、字段名）见 config/locales 中的 template.<模板>.*
+DOMAIN_TEMPLATES: Dict[str, Dict[str, Any]] = {
+    "saas": {
+        "aliases": ["saas", "software", "软件", "saas产品", "软件产品"],
+        "slots": [
+            ("target_user", 10, "target"),
+            ("core_pain", 9, "problem"),
+            ("pricing_model", 8, "business"),
+            ("integrations", 7, "integration"),
+            ("acquisition_channel", 7, "growth"),
+            ("compliance", 6, "constraints"),
+        ],
+        "fields": ["pricing_model", "integrations", "acquisition_channel", "compliance"],
+    },
+    "research": {
+        "aliases": ["research", "research proposal", "研究", "科研", "课题", "研究提案"],
+        "slots": [
+            ("research_question", 10, "problem"),
+            ("prior_work", 9, "context"),
+            ("methodology", 8, "method"),
+            ("data_sources", 8, "data"),
+            ("evaluation", 7, "metrics"),
+            ("ethics", 6, "constraints"),
+        ],
+        "fields": ["research_question", "methodology", "evaluation", "ethics"],
+    },
+    "hardware": {
+        "aliases": ["hardware", "iot", "device", "硬件", "智能硬件", "物联网", "硬件项目"],
+        "slots": [
+            ("target_user", 10, "target"),
+            ("form_factor", 9, "design"),
+            ("bom_cost", 8, "business"),
+            ("manufacturing", 7, "supply"),
+            ("certification", 7, "constraints"),
+            ("connectivity", 6, "integration"),
+        ],
+        "fields": ["form_factor", "bom_cost", "manufacturing", "certification"],
+    },
+    "marketing": {
+        "aliases": ["marketing", "campaign", "marketing campaign", "营销", "推广", "营销活动", "市场活动"],
+        "slots": [
+            ("campaign_goal", 10, "goal"),
+            ("target_audience", 9, "target"),
+            ("key_message", 8, "message"),
+            ("channels", 8, "channel"),
+            ("budget_timeline", 7, "constraints"),
+            ("success_metrics", 7, "metrics"),
+        ],
+        "fields": ["campaign_goal", "key_message", "channels", "budget_timeline"],
+    },
+}
+
+
+def _localize(name: str, language: Optional[str]) -> Dict[str, Any]:
+    template = DOMAIN_TEMPLATES[name]
+    return {
+        "name": name,
+        "label": t(f"template.{name}.label", language),
+        "slots": [
+            {"slot_name": slot, "question": t(f"template.{name}.question.{slot}", language), "priority": priority, "type": kind}
+            for slot, priority, kind in template["slots"]
+        ],
+        "fields": [(field, t(f"template.{name}.field.{field}", language)) for field in template["fields"]],
+    }
+
+
+def get_domain_template(domain: Optional[str], language: Optional[str] = None) -> Optional[Dict[str, Any]]:
+    """按模板名、任一语言的展示名或别名（不区分大小写）查找领域模板，文案使用指定语言"""
+    name = (domain or "").strip().lower()
+    if not name:
+        return None
+    for key, template in DOMAIN_TEMPLATES.items():
+        labels = [t(f"template.{key}.label", lang).lower() for lang in SUPPORTED_LANGUAGES]
+        if name == key or name in labels or name in template["aliases"]:
+            return _localize(key, language)
+    return None
+
+
+def list_domain_templates(language: Optional[str] = None) -> List[Dict[str, Any]]:
+    templates = [_localize(key, language) for key in DOMAIN_TEMPLATES]
+    return [
+        {
+            "name": template["name"],
+            "label": template["label"],
+            "slots": [slot["slot_name"] for slot in template["slots"]],
+            "fields": [{"key": field, "label": label} for field, label in template["fields"]],
+        }
+        for template in templates
+    ]
+
+
+def domain_field_labels(domain: Optional[str], language: Optional[str] = None) -> Dict[str, str]:
+    template = get_domain_template(domain, language)
+    return dict(template["fields"]) if template else {}
+
+
+def clarifier_guidance(template: Optional[Dict[str, Any]]) -> str:
+    """提示澄清智能体优先覆盖模板槽位（用于 ${domain_slots}）"""
+    if not template:
+        return ""
+    lines = [f"- {slot['slot_name']}: {slot['question']}" for slot in template["slots"]]
+    return (
+        f"Domain: {template['label']}. Prefer covering these slots (reuse the slot_name values):\n"
+        + "\n".join(lines) + "\n"
+    )
+
+
+def summarizer_guidance(template: Optional[Dict[str, Any]]) -> str:
+    """要求总结智能体额外输出 domain_fields（用于 ${domain_fields}）"""
+    if not template:
+        return ""
+    keys = ", ".join(f"{key} ({label})" for key, label in template["fields"])
+    return f" Also include domain_fields: an object with string values for keys {keys}."
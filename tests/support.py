@@ -0,0 +1,24 @@
+"""
+测试公共设置
+数据、配置与缓存目录指向临时目录，并启用离线模式，避免读写用户数据或访问模型提供商；
+必须在导入 config 与 src 之前导入
+"""
+
+import os
+import sys
+import tempfile
+from pathlib import Path
+
+PROJECT_ROOT = Path(__file__).resolve().parents[1]
+if str(PROJECT_ROOT) not in sys.path:
+    sys.path.insert(0, str(PROJECT_ROOT))
+
+TEST_ROOT = Path(tempfile.mkdtemp(prefix="ideaarchitect-tests-"))
+
+os.environ["DATA_DIR"] = str(TEST_ROOT / "data")
+os.environ["CONFIG_DIR"] = str(TEST_ROOT / "config")
+os.environ["CACHE_DIR"] = str(TEST_ROOT / "cache")
+os.environ["IDEAARCHITECT_OFFLINE"] = "1"
+for name in ("OPENAI_API_KEY", "ANTHROPIC_API_KEY", "DEEPSEEK_API_KEY", "QWEN_API_KEY", "DASHSCOPE_API_KEY",
+             "GEMINI_API_KEY", "GOOGLE_API_KEY", "MODEL_PROVIDER", "IDEAARCHITECT_VCR_MODE"):
+    os.environ.pop(name, None)
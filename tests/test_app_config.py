@@ -0,0 +1,60 @@
+"""应用配置：显式传入的存储目录不被环境变量或平台默认目录覆盖"""
+
+import contextlib
+import io
+import os
+import stat
+import tempfile
+import unittest
+from pathlib import Path
+
+import support  # noqa: F401
+
+from config.app_config import AppConfig
+
+
+class AppConfigLocationTest(unittest.TestCase):
+
+    def setUp(self):
+        self.root = Path(tempfile.mkdtemp(prefix="ideaarchitect-config-"))
+
+    def test_explicit_dirs_are_kept(self):
+        config = AppConfig(
+            data_dir=self.root / "data",
+            config_dir=self.root / "config",
+            cache_dir=self.root / "cache",
+        )
+        self.assertEqual(config.data_dir, self.root / "data")
+        self.assertEqual(config.config_dir, self.root / "config")
+        self.assertEqual(config.cache_dir, self.root / "cache")
+        # 未传入的目录仍从显式的 data_dir 推导
+        self.assertEqual(config.projects_dir, self.root / "data" / "projects")
+        self.assertEqual(config.logs_dir, self.root / "data" / "logs")
+
+    def test_env_used_when_not_passed(self):
+        config = AppConfig(config_dir=self.root / "config")
+        self.assertEqual(config.data_dir, Path(os.environ["DATA_DIR"]))
+        self.assertEqual(config.cache_dir, Path(os.environ["CACHE_DIR"]))
+
+
+class SettingsFileTest(unittest.TestCase):
+
+    def setUp(self):
+        self.config = AppConfig(config_dir=Path(tempfile.mkdtemp(prefix="ideaarchitect-settings-")))
+
+    def test_saved_file_is_private(self):
+        self.config.apply_settings({"openai_api_key": "sk-test-key-0000000000"})
+        path = self.config.save_settings()
+        if os.name == "posix":
+            self.assertEqual(stat.S_IMODE(path.stat().st_mode), 0o600)
+
+    def test_load_errors_are_logged_not_printed(self):
+        self.config.settings_file.write_text("{not json", encoding="utf-8")
+        stdout = io.StringIO()
+        with contextlib.redirect_stdout(stdout), self.assertLogs("config.app_config", level="WARNING"):
+            self.config.load_settings()
+        self.assertEqual(stdout.getvalue(), "")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,61 @@
+"""审计日志：滚动、筛选与密钥遮盖"""
+
+import unittest
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.utils import audit
+from src.utils.audit import audited, is_read_only_command, mask_secret, read_audit_log, record_audit, summarize_value
+
+
+class AuditLogTest(unittest.TestCase):
+
+    def setUp(self):
+        self._settings = (app_config.audit_max_size_mb, app_config.audit_backup_count)
+        for file in audit._audit_files():
+            file.unlink()
+        # 下次写入时重新打开文件
+        audit._handler_key = None
+
+    def tearDown(self):
+        app_config.audit_max_size_mb, app_config.audit_backup_count = self._settings
+
+    def test_rotates_and_reads_newest_first(self):
+        app_config.audit_max_size_mb = 0.002
+        app_config.audit_backup_count = 2
+        for index in range(200):
+            record_audit("command", "api_test", session_id=f"s{index % 2}", index=index)
+
+        files = audit._audit_files()
+        self.assertEqual(len(files), 3)
+        self.assertTrue(all(file.stat().st_size <= 0.002 * 1024 * 1024 + 200 for file in files))
+
+        entries = read_audit_log(limit=5)
+        self.assertEqual([entry["index"] for entry in entries], [199, 198, 197, 196, 195])
+        odd = read_audit_log(session_id="s1", limit=3)
+        self.assertEqual([entry["index"] for entry in odd], [199, 197, 195])
+        self.assertEqual(read_audit_log(kind="agent"), [])
+
+    def test_command_arguments_mask_secrets(self):
+        def api_save_key(provider, key, settings=None):
+            return {"success": False, "error": "x", "error_info": {"code": "invalid_key"}}
+
+        audited(api_save_key)("openai", "sk-abcdefghijklmnop1234", {"openai_api_key": "sk-zyxwvutsrqponm9876"})
+        entry = read_audit_log(action="api_save_key")[0]
+        self.assertFalse(entry["success"])
+        self.assertEqual(entry["error_code"], "invalid_key")
+        self.assertEqual(entry["arguments"]["key"], "sk-a…1234")
+        self.assertEqual(entry["arguments"]["settings"]["openai_api_key"], "sk-z…9876")
+
+    def test_helpers(self):
+        self.assertEqual(mask_secret("short"), "****")
+        self.assertIsNone(mask_secret(""))
+        self.assertTrue(summarize_value("x" * 500).endswith("字符）"))
+        self.assertTrue(is_read_only_command("api_get_workflow_status"))
+        self.assertTrue(is_read_only_command("api_list_sessions"))
+        self.assertFalse(is_read_only_command("api_delete_session"))
+
+
+if __name__ == "__main__":
+    unittest.main()
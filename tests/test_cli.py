@@ -0,0 +1,49 @@
+"""命令行：回答文件在创建会话之前校验"""
+
+import io
+import json
+import tempfile
+import unittest
+from contextlib import redirect_stderr
+from pathlib import Path
+from typing import Tuple
+from unittest import mock
+
+import support  # noqa: F401
+
+import cli
+from src.utils.storage import SessionStorage
+
+
+class CliAnswersTest(unittest.TestCase):
+
+    def run_cli(self, answers_path: Path) -> Tuple[int, str]:
+        stderr = io.StringIO()
+        with mock.patch("sys.stdin", io.StringIO("A marketplace for local bakers")), redirect_stderr(stderr):
+            code = cli.main(["-", "-q", "--answers", str(answers_path)])
+        return code, stderr.getvalue()
+
+    def test_bad_answers_file_fails_before_creating_a_session(self):
+        directory = Path(tempfile.mkdtemp(dir=support.TEST_ROOT))
+        (directory / "list.json").write_text(json.dumps(["not", "a", "dict"]), encoding="utf-8")
+        (directory / "broken.json").write_text("{not json", encoding="utf-8")
+        before = len(SessionStorage().list_sessions())
+
+        for name in ("missing.json", "broken.json", "list.json"):
+            with self.subTest(name=name):
+                code, stderr = self.run_cli(directory / name)
+                self.assertEqual(code, 2)
+                self.assertIn(name, stderr)
+        self.assertEqual(len(SessionStorage().list_sessions()), before)
+
+    def test_answers_are_submitted(self):
+        path = Path(tempfile.mkdtemp(dir=support.TEST_ROOT)) / "answers.json"
+        path.write_text(json.dumps({"target_user": "Home bakers", "budget": 500}), encoding="utf-8")
+        with mock.patch("sys.stdout", io.StringIO()) as stdout:
+            code, _ = self.run_cli(path)
+        self.assertEqual(code, 0)
+        self.assertIn("Home bakers", stdout.getvalue())
+
+
+if __name__ == "__main__":
+    unittest.main()
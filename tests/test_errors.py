@@ -0,0 +1,91 @@
+"""结构化错误：异常归类、失败响应与提供商错误解析"""
+
+import unittest
+from datetime import datetime, timedelta, timezone
+from email.utils import format_datetime
+from types import SimpleNamespace
+
+import support  # noqa: F401
+
+from src.utils.errors import AppError, ErrorCode, app_error, classify_exception, error_response, forward_error
+from src.utils.providers import ModelError, ModelErrorKind, classify_response, parse_retry_after
+
+
+class ClassifyExceptionTest(unittest.TestCase):
+
+    def test_model_error_keeps_provider_and_retryable(self):
+        app = classify_exception(ModelError(ModelErrorKind.RATE_LIMITED, "slow down", "openai", 429))
+        self.assertEqual(app.code, ErrorCode.RATE_LIMITED)
+        self.assertTrue(app.retryable)
+        self.assertEqual(app.provider, "openai")
+
+        app = classify_exception(ModelError(ModelErrorKind.INVALID_KEY, "bad key", "anthropic", 401))
+        self.assertEqual(app.code, ErrorCode.INVALID_KEY)
+        self.assertFalse(app.retryable)
+
+    def test_builtin_exceptions(self):
+        self.assertEqual(classify_exception(ValueError("x")).code, ErrorCode.INVALID_INPUT)
+        self.assertEqual(classify_exception(FileNotFoundError("x")).code, ErrorCode.STORAGE)
+        self.assertEqual(classify_exception(RuntimeError("x")).code, ErrorCode.INTERNAL)
+        original = AppError(ErrorCode.NOT_FOUND, "missing")
+        self.assertIs(classify_exception(original), original)
+
+    def test_every_model_error_kind_has_a_code(self):
+        for kind in ModelErrorKind:
+            self.assertEqual(classify_exception(ModelError(kind, "x")).code.value, kind.value)
+
+
+class ErrorResponseTest(unittest.TestCase):
+
+    def test_error_response(self):
+        result = error_response(TimeoutError("read timed out"), "生成失败", session_id="s1")
+        self.assertFalse(result["success"])
+        self.assertEqual(result["error"], "生成失败")
+        self.assertEqual(result["error_info"], {
+            "code": "timeout", "message": "生成失败", "retryable": True, "provider": None, "session_id": "s1",
+        })
+
+    def test_app_error_and_forward(self):
+        result = app_error(ErrorCode.NOT_FOUND, "会话不存在", "s1")
+        self.assertEqual(result["error_info"]["code"], "not_found")
+        forwarded = forward_error(result, "失败", session_id="s2")
+        self.assertEqual(forwarded["error_info"]["code"], "not_found")
+        self.assertEqual(forwarded["error_info"]["session_id"], "s2")
+        plain = forward_error({"success": False}, "失败")
+        self.assertEqual(plain["error"], "失败")
+        self.assertEqual(plain["error_info"]["code"], "internal")
+
+
+class ProviderErrorTest(unittest.TestCase):
+
+    def test_parse_retry_after(self):
+        self.assertEqual(parse_retry_after("12"), 12.0)
+        self.assertEqual(parse_retry_after("-3"), 0.0)
+        self.assertIsNone(parse_retry_after(None))
+        self.assertIsNone(parse_retry_after("soon"))
+        later = format_datetime(datetime.now(timezone.utc) + timedelta(seconds=30), usegmt=True)
+        self.assertAlmostEqual(parse_retry_after(later), 30, delta=2)
+        earlier = format_datetime(datetime.now(timezone.utc) - timedelta(seconds=30), usegmt=True)
+        self.assertEqual(parse_retry_after(earlier), 0.0)
+
+    def test_classify_response(self):
+        def response(status, text="", headers=None):
+            return SimpleNamespace(status_code=status, text=text, headers=headers or {})
+
+        cases = [
+            (response(429, headers={"Retry-After": "5"}), ModelErrorKind.RATE_LIMITED),
+            (response(401), ModelErrorKind.INVALID_KEY),
+            (response(400, "This model's maximum context length is 8192 tokens"), ModelErrorKind.CONTEXT_TOO_LONG),
+            (response(504), ModelErrorKind.TIMEOUT),
+            (response(503), ModelErrorKind.UNAVAILABLE),
+            (response(400, "bad field"), ModelErrorKind.BAD_REQUEST),
+        ]
+        for resp, kind in cases:
+            error = classify_response("openai", resp)
+            self.assertEqual(error.kind, kind, resp)
+            self.assertEqual(error.status_code, resp.status_code)
+        self.assertEqual(classify_response("openai", cases[0][0]).retry_after, 5.0)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,60 @@
+"""交接文本：澄清结果与总结的标签跟随输出语言"""
+
+import re
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.api.questioning_api import _build_enriched_idea, _format_summary_text
+
+CJK = re.compile(r"[　-ヿ一-鿿＀-￯]")
+
+SUMMARY = {
+    "title": "Bakery marketplace",
+    "refined_idea": "Connect home bakers with neighbours",
+    "user_segments": ["home bakers", "neighbours"],
+    "risks": ["food safety"],
+}
+
+
+def _session(language=None):
+    seed = {"raw_text": "A marketplace for local bakers"}
+    if language:
+        seed["language"] = language
+    return {
+        "idea_seed": seed,
+        "questions": [
+            {"question": "Who pays?", "answer": "Buyers pay per order"},
+            {"question": "Unanswered", "answer": None},
+        ],
+    }
+
+
+class HandoffTextTest(unittest.TestCase):
+
+    def test_english_labels(self):
+        enriched = _build_enriched_idea(_session("en-US"))
+        self.assertIn("Clarification Results:", enriched)
+        self.assertIn("A: Buyers pay per order", enriched)
+        self.assertNotIn("Unanswered", enriched)
+
+        text = _format_summary_text(SUMMARY, "en-US")
+        self.assertIn("Title: Bakery marketplace", text)
+        self.assertIn("Target Users: home bakers; neighbours", text)
+        self.assertIsNone(CJK.search(enriched + text))
+
+    def test_chinese_labels(self):
+        enriched = _build_enriched_idea(_session("zh-CN"))
+        self.assertIn("澄清结果：", enriched)
+        self.assertIn("答：Buyers pay per order", enriched)
+        self.assertIn("目标用户：home bakers；neighbours", _format_summary_text(SUMMARY, "zh-CN"))
+
+    def test_seed_without_language_uses_setting(self):
+        with mock.patch.object(app_config, "output_language", "ja-JP"):
+            self.assertIn("確認結果：", _build_enriched_idea(_session()))
+
+
+if __name__ == "__main__":
+    unittest.main()
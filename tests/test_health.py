@@ -0,0 +1,60 @@
+"""健康检查：提供商探测使用模型列表接口，后台检查默认不请求提供商"""
+
+import os
+import unittest
+from types import SimpleNamespace
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.utils import audit, health, providers
+
+
+class HealthCheckTest(unittest.TestCase):
+
+    def setUp(self):
+        patcher = mock.patch.object(app_config, "offline", False)
+        patcher.start()
+        self.addCleanup(patcher.stop)
+        env = mock.patch.dict(os.environ, {"OPENAI_API_KEY": "sk-test-key-0000000000"})
+        env.start()
+        self.addCleanup(env.stop)
+        health._last_health = None
+
+    def test_probe_uses_models_endpoint(self):
+        response = SimpleNamespace(status_code=200, text="{}", headers={})
+        with mock.patch.object(providers.requests, "get", return_value=response) as get, \
+                mock.patch.object(providers.requests, "post") as post, \
+                mock.patch.object(providers, "acquire_provider_slot") as acquire:
+            result = providers.probe_provider("openai")
+        self.assertTrue(result["success"])
+        self.assertTrue(get.call_args.args[0].endswith("/v1/models"))
+        post.assert_not_called()
+        acquire.assert_called_once()
+        entry = audit.read_audit_log(kind="model_call", action="openai", limit=1)[0]
+        self.assertTrue(entry["probe"])
+
+    def test_probe_classifies_errors(self):
+        response = SimpleNamespace(status_code=401, text="invalid key", headers={})
+        with mock.patch.object(providers.requests, "get", return_value=response):
+            result = providers.probe_provider("openai")
+        self.assertFalse(result["success"])
+        self.assertEqual(result["error_code"], "invalid_key")
+
+    def test_background_check_skips_providers(self):
+        with mock.patch.object(providers.requests, "get") as get, \
+                mock.patch.object(providers.requests, "post") as post:
+            result = health.run_health_check(include_providers=False)
+        get.assert_not_called()
+        post.assert_not_called()
+        self.assertEqual(result["status"], "ok")
+        self.assertIsNone(result["providers_checked_at"])
+        self.assertTrue(result["features"]["ai_models"])
+
+    def test_provider_checks_opt_in(self):
+        self.assertEqual(app_config.provider_check_interval, 0)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,52 @@
+"""提示词模板：默认模板把领域与上下文提示传给模型"""
+
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.api.questioning_api import _prompt_variables, api_regenerate_summary, api_start_clarification_session
+from src.utils.mock_provider import MockProvider
+from src.utils.prompts import render_prompt
+
+
+class DefaultPromptTest(unittest.TestCase):
+
+    def test_rendered_clarifier_prompt(self):
+        seed = {"raw_text": "A marketplace for local bakers", "domain": "ecommerce", "context_hints": ["B2C", "Berlin"]}
+        prompt = render_prompt("clarifier.user", _prompt_variables(seed))
+        self.assertIn("Idea: A marketplace for local bakers", prompt)
+        self.assertIn("Domain: ecommerce", prompt)
+        self.assertIn("Context: B2C, Berlin", prompt)
+        self.assertNotIn("$", prompt)
+
+        prompt = render_prompt("clarifier.user", _prompt_variables({"raw_text": "An idea"}))
+        self.assertIn("Domain: unspecified", prompt)
+        self.assertIn("Context: none", prompt)
+
+    def test_domain_and_context_reach_the_model(self):
+        prompts = []
+        complete = MockProvider.complete
+
+        def capture(provider, system_prompt, user_content, agent=None):
+            prompts.append(user_content)
+            return complete(provider, system_prompt, user_content, agent)
+
+        with mock.patch.object(app_config, "offline", False), \
+                mock.patch.object(app_config, "default_model_provider", "mock"), \
+                mock.patch.object(app_config, "response_cache_enabled", False), \
+                mock.patch.object(MockProvider, "complete", capture):
+            session_id = api_start_clarification_session({
+                "raw_text": "A marketplace for local bakers", "domain": "ecommerce", "context_hints": ["Berlin"],
+            })["session_id"]
+            api_regenerate_summary(session_id)
+
+        self.assertEqual(len(prompts), 2)
+        for prompt in prompts:
+            self.assertIn("Domain: ecommerce", prompt)
+            self.assertIn("Context: Berlin", prompt)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,62 @@
+"""提供商回退顺序：读取 provider_order 设置，回退时发布事件"""
+
+import os
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.api import questioning_api
+from src.api.system_api import api_save_config
+from src.utils.event_bus import EventTypes, event_bus
+from src.utils.providers import ModelError, ModelErrorKind
+
+
+class ProviderOrderTest(unittest.TestCase):
+
+    def setUp(self):
+        for name, value in (("offline", False), ("default_model_provider", "openai"), ("response_cache_enabled", False)):
+            patcher = mock.patch.object(app_config, name, value)
+            patcher.start()
+            self.addCleanup(patcher.stop)
+        env = mock.patch.dict(os.environ, {"OPENAI_API_KEY": "sk-test-key-0000000000", "DEEPSEEK_API_KEY": "sk-test-key-1111111111"})
+        env.start()
+        self.addCleanup(env.stop)
+
+    def test_order_comes_from_settings(self):
+        with mock.patch.object(app_config, "provider_order", ["openai", "deepseek", "unknown"]):
+            self.assertEqual(questioning_api._provider_order(None), ["openai", "deepseek"])
+            self.assertEqual(questioning_api._configured_providers(None), ["openai", "deepseek"])
+            self.assertEqual(questioning_api._provider_order({"providers": ["deepseek"]}), ["deepseek"])
+
+    def test_save_rejects_unknown_providers(self):
+        result = api_save_config({"provider_order": ["openai", "nope"]})
+        self.assertFalse(result["success"])
+        self.assertEqual(result["error_info"]["code"], "invalid_input")
+
+    def test_fallback_emits_event(self):
+        calls = []
+
+        def chat(settings, *args, **kwargs):
+            calls.append(settings["provider"])
+            if settings["provider"] == "openai":
+                raise ModelError(ModelErrorKind.RATE_LIMITED, "slow down", "openai", 429)
+            return '{"questions": [{"question": "Who pays?", "type": "business", "priority": 8, "slot_name": "payer"}]}'
+
+        events = []
+        event_bus.subscribe(EventTypes.SYSTEM_WARNING, events.append)
+        self.addCleanup(event_bus.unsubscribe, EventTypes.SYSTEM_WARNING, events.append)
+        with mock.patch.object(app_config, "provider_order", ["openai", "deepseek"]), \
+                mock.patch.object(questioning_api, "chat_completion", chat):
+            session_id = questioning_api.api_start_clarification_session({"raw_text": "A marketplace for bakers"})["session_id"]
+
+        self.assertEqual(calls, ["openai", "deepseek"])
+        fallbacks = [event.data for event in events if event.data.get("fallback")]
+        self.assertEqual(len(fallbacks), 1)
+        self.assertEqual((fallbacks[0]["agent"], fallbacks[0]["provider"]), ("clarifier", "deepseek"))
+        self.assertEqual(fallbacks[0]["session_id"], session_id)
+
+
+if __name__ == "__main__":
+    unittest.main()
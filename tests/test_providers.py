@@ -0,0 +1,59 @@
+"""模型请求：json_mode 被拒绝时才去掉约束重发"""
+
+import os
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.utils import providers
+from src.utils.providers import ModelError, ModelErrorKind, chat_completion, get_provider_settings
+
+RESPONSE = {"choices": [{"message": {"content": "{}"}}]}
+
+
+class JsonModeFallbackTest(unittest.TestCase):
+
+    def setUp(self):
+        for name, value in (("offline", False), ("response_cache_enabled", False), ("retry_delay", 0.0)):
+            patcher = mock.patch.object(app_config, name, value)
+            patcher.start()
+            self.addCleanup(patcher.stop)
+        env = mock.patch.dict(os.environ, {"OPENAI_API_KEY": "sk-test-key-0000000000"})
+        env.start()
+        self.addCleanup(env.stop)
+        self.settings = get_provider_settings("openai")
+
+    def call(self, failure):
+        bodies = []
+
+        def post_once(provider, url, headers, body, timeout):
+            bodies.append(body)
+            if len(bodies) == 1:
+                raise failure
+            return RESPONSE
+
+        with mock.patch.object(providers, "_post_once", post_once):
+            try:
+                chat_completion(self.settings, "Return JSON only.", "user", json_mode=True, max_retries=0)
+            except ModelError:
+                pass
+        return bodies
+
+    def test_rejected_response_format_is_retried_without_json_mode(self):
+        error = ModelError(ModelErrorKind.BAD_REQUEST, "HTTP 400: 'response_format' is not supported", "openai", 400)
+        bodies = self.call(error)
+        self.assertEqual(len(bodies), 2)
+        self.assertIn("response_format", bodies[0])
+        self.assertNotIn("response_format", bodies[1])
+
+    def test_other_client_errors_are_not_retried(self):
+        for status, text in ((404, "The model `gpt-x` does not exist"), (400, "Invalid value for 'temperature'")):
+            with self.subTest(status=status):
+                error = ModelError(ModelErrorKind.BAD_REQUEST, f"HTTP {status}: {text}", "openai", status)
+                self.assertEqual(len(self.call(error)), 1)
+
+
+if __name__ == "__main__":
+    unittest.main()
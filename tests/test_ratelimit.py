@@ -0,0 +1,76 @@
+"""提供商限流：滑动窗口额度计算与排队放行"""
+
+import time
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import ProviderRateLimit, app_config
+from src.utils.ratelimit import (
+    WINDOW_SECONDS,
+    ProviderRateLimiter,
+    RateLimitTimeout,
+    acquire_provider_slot,
+    estimate_tokens,
+)
+
+
+class RateLimitTest(unittest.TestCase):
+
+    def setUp(self):
+        patcher = mock.patch.object(app_config, "provider_rate_limits", {})
+        patcher.start()
+        self.addCleanup(patcher.stop)
+
+    def limiter(self, rpm=0, tpm=0):
+        app_config.provider_rate_limits["test"] = ProviderRateLimit(requests_per_minute=rpm, tokens_per_minute=tpm)
+        return ProviderRateLimiter("test")
+
+    def test_estimate_tokens(self):
+        self.assertEqual(estimate_tokens("a" * 40, "b" * 8, max_tokens=100), 112)
+        self.assertEqual(estimate_tokens(None, max_tokens=None), 0)
+
+    def test_request_window(self):
+        limiter = self.limiter(rpm=2)
+        limiter._granted.extend([(100.0, 0), (110.0, 0)])
+        # 第三次请求要等最早的一次移出窗口
+        self.assertAlmostEqual(limiter._wait_for_capacity(120.0, 0), 100.0 + WINDOW_SECONDS - 120.0)
+        limiter._prune(100.0 + WINDOW_SECONDS)
+        self.assertEqual(len(limiter._granted), 1)
+        self.assertEqual(limiter._wait_for_capacity(100.0 + WINDOW_SECONDS, 0), 0.0)
+
+    def test_token_window(self):
+        limiter = self.limiter(tpm=1000)
+        limiter._granted.extend([(100.0, 600), (110.0, 300)])
+        self.assertEqual(limiter._wait_for_capacity(120.0, 100), 0.0)
+        # 需要等到第一次请求的 600 token 移出窗口
+        self.assertAlmostEqual(limiter._wait_for_capacity(120.0, 200), 100.0 + WINDOW_SECONDS - 120.0)
+        # 超过上限的单次请求按上限计，窗口清空后即可放行
+        self.assertAlmostEqual(limiter._wait_for_capacity(120.0, 5000), 110.0 + WINDOW_SECONDS - 120.0)
+
+    def test_unconfigured_provider_not_limited(self):
+        self.assertEqual(acquire_provider_slot("unconfigured", 10_000), 0.0)
+        self.assertEqual(ProviderRateLimiter("unconfigured")._wait_for_capacity(0.0, 10_000), 0.0)
+
+    def test_deadline(self):
+        limiter = self.limiter(rpm=1)
+        limiter.acquire(0)
+        with self.assertRaises(RateLimitTimeout):
+            limiter.acquire(0, deadline=time.monotonic() + 0.05)
+        self.assertEqual(limiter.snapshot()["waiting"], 0)
+        self.assertEqual(limiter.snapshot()["requests"], 1)
+
+    def test_sessions_take_turns(self):
+        limiter = self.limiter(rpm=100)
+        limiter._waiting.extend([(5, "a"), (6, "a"), (7, "b")])
+        # 会话 a 刚被放行过，后排队的会话 b 优先；从未放行过的会话最优先
+        limiter._last_served["a"] = 4
+        self.assertEqual(limiter._next_waiter(), (7, "b"))
+        limiter._last_served["b"] = 8
+        self.assertEqual(limiter._next_waiter(), (5, "a"))
+        limiter._waiting.append((9, "c"))
+        self.assertEqual(limiter._next_waiter(), (9, "c"))
+
+if __name__ == "__main__":
+    unittest.main()
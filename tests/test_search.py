@@ -0,0 +1,70 @@
+"""会话检索：查询词拆分与相关度计分"""
+
+import unittest
+
+import support  # noqa: F401
+
+from src.utils.search import parse_query, score_session
+
+
+def _session(**fields):
+    session = {
+        "id": "s1",
+        "title": "",
+        "idea_seed": {"raw_text": "", "context_hints": []},
+        "questions": [],
+        "summary": {},
+        "summary_history": [],
+    }
+    session.update(fields)
+    return session
+
+
+class ParseQueryTest(unittest.TestCase):
+
+    def test_words_and_phrases(self):
+        self.assertEqual(parse_query('Coffee  "Home Bakers" coffee'), ["coffee", "home bakers"])
+
+    def test_empty(self):
+        self.assertEqual(parse_query(""), [])
+        self.assertEqual(parse_query(None), [])
+        self.assertEqual(parse_query('  ""  '), [])
+
+    def test_unbalanced_quote(self):
+        self.assertEqual(parse_query('"home bakers'), ["home", "bakers"])
+
+
+class ScoreSessionTest(unittest.TestCase):
+
+    def test_all_terms_required(self):
+        session = _session(idea_seed={"raw_text": "A marketplace for home bakers", "context_hints": []})
+        self.assertIsNotNone(score_session(session, ["bakers"]))
+        self.assertIsNone(score_session(session, ["bakers", "coffee"]))
+
+    def test_title_outranks_history(self):
+        in_title = _session(title="Coffee subscription")
+        in_history = _session(summary_history=[{"summary": {"overview": "coffee"}}, {"summary": {}}])
+        self.assertGreater(score_session(in_title, ["coffee"])["score"], score_session(in_history, ["coffee"])["score"])
+
+    def test_current_summary_not_counted_as_history(self):
+        session = _session(summary_history=[{"summary": {"overview": "coffee"}}])
+        self.assertIsNone(score_session(session, ["coffee"]))
+
+    def test_repeated_hits_diminish(self):
+        once = score_session(_session(title="coffee"), ["coffee"])["score"]
+        twice = score_session(_session(title="coffee coffee"), ["coffee"])["score"]
+        self.assertGreater(twice, once)
+        self.assertLess(twice, 2 * once)
+
+    def test_answers_and_snippets(self):
+        session = _session(questions=[
+            {"question": "Who pays?", "answer": "Small cafés pay a monthly fee"},
+            {"question": "Unanswered", "answer": None},
+        ])
+        result = score_session(session, ["monthly"])
+        self.assertEqual(result["snippets"][0]["field"], "answer")
+        self.assertIn("monthly", result["snippets"][0]["text"])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,108 @@
+"""会话包导入：拒绝带有路径或缺少 id 的条目，正常的包可以导回"""
+
+import json
+import os
+import tempfile
+import unittest
+import zipfile
+from pathlib import Path
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.api.export_api import BUNDLE_FORMAT, BUNDLE_VERSION, api_export_session_bundle, api_import_session_bundle
+from src.api.questioning_api import api_set_confidential_terms, api_start_clarification_session
+from src.utils.storage import SessionStorage
+
+
+def write_bundle(directory: Path, session, project=None, workflow=None) -> Path:
+    path = directory / "bundle.zip"
+    with zipfile.ZipFile(path, "w") as bundle:
+        bundle.writestr("manifest.json", json.dumps({"format": BUNDLE_FORMAT, "version": BUNDLE_VERSION}))
+        bundle.writestr("session.json", json.dumps(session))
+        if project is not None:
+            bundle.writestr("project.json", json.dumps(project))
+        if workflow is not None:
+            bundle.writestr("workflow.json", json.dumps(workflow))
+    return path
+
+
+class SessionBundleImportTest(unittest.TestCase):
+
+    def setUp(self):
+        self.tmp = Path(tempfile.mkdtemp(dir=support.TEST_ROOT))
+
+    def assert_rejected(self, path: Path):
+        result = api_import_session_bundle(str(path))
+        self.assertFalse(result["success"])
+        self.assertEqual(result["error_info"]["code"], "invalid_input")
+
+    def test_hostile_ids_are_rejected(self):
+        outside = app_config.data_dir.parent / "escaped"
+        cases = [
+            ({"id": "../../escaped"}, None, None),
+            ({"id": "clar_ok"}, {"id": "../../escaped"}, None),
+            ({"id": "clar_ok"}, {"name": "no id"}, None),
+            ({"id": "clar_ok"}, None, {"status": "running"}),
+            (["not", "a", "session"], None, None),
+        ]
+        for session, project, workflow in cases:
+            with self.subTest(session=session, project=project, workflow=workflow):
+                self.assert_rejected(write_bundle(self.tmp, session, project, workflow))
+        self.assertFalse(outside.with_suffix(".json").exists())
+        self.assertIsNone(SessionStorage().load_session("clar_ok"))
+
+    def test_hostile_references_are_rejected(self):
+        for field in ("project_id", "workflow_session_id"):
+            with self.subTest(field=field):
+                self.assert_rejected(write_bundle(self.tmp, {"id": "clar_ref", field: "../../escaped"}))
+        self.assertIsNone(SessionStorage().load_session("clar_ref"))
+
+    def test_export_ignores_project_outside_projects_dir(self):
+        secret = app_config.data_dir.parent / "outside.json"
+        secret.write_text(json.dumps({"id": "outside", "token": "do not export"}), encoding="utf-8")
+        session_id = api_start_clarification_session({"raw_text": "A marketplace for local bakers"})["session_id"]
+        storage = SessionStorage()
+        session = storage.load_session(session_id)
+        session["project_id"] = os.path.relpath(secret.with_suffix(""), app_config.projects_dir)
+        self.assertTrue((app_config.projects_dir / f"{session['project_id']}.json").exists())
+        storage.save_session(session_id, session)
+
+        exported = api_export_session_bundle(session_id, str(self.tmp / "escape.zip"))
+        self.assertTrue(exported["success"])
+        self.assertNotIn("project.json", exported["data"]["files"])
+
+    def test_export_redacts_by_default(self):
+        started = api_start_clarification_session({
+            "raw_text": "Project Falcon: a marketplace for bakers, contact jane@example.com",
+        })
+        session_id = started["session_id"]
+        api_set_confidential_terms(session_id, ["Project Falcon"])
+
+        exported = api_export_session_bundle(session_id, str(self.tmp / "redacted.zip"))
+        self.assertTrue(exported["data"]["redacted"])
+        with zipfile.ZipFile(exported["data"]["path"]) as bundle:
+            text = bundle.read("session.json").decode("utf-8")
+            self.assertTrue(json.loads(bundle.read("manifest.json"))["redacted"])
+        self.assertNotIn("jane@example.com", text)
+        self.assertNotIn("Project Falcon", text)
+
+        raw = api_export_session_bundle(session_id, str(self.tmp / "raw.zip"), include_sensitive=True)
+        self.assertFalse(raw["data"]["redacted"])
+        with zipfile.ZipFile(raw["data"]["path"]) as bundle:
+            self.assertIn("jane@example.com", bundle.read("session.json").decode("utf-8"))
+
+    def test_round_trip(self):
+        started = api_start_clarification_session({"raw_text": "A marketplace for local bakers to sell surplus bread"})
+        exported = api_export_session_bundle(started["session_id"], str(self.tmp / "export.zip"))
+        self.assertTrue(exported["success"])
+
+        imported = api_import_session_bundle(exported["data"]["path"])
+        self.assertTrue(imported["success"])
+        # 本地已有同 ID 的会话时分配新 ID
+        self.assertNotEqual(imported["session_id"], started["session_id"])
+        self.assertEqual(imported["data"]["imported_from"], started["session_id"])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,71 @@
+"""会话时间线：智能体执行与模型请求（重试、token 用量）来自审计日志"""
+
+import os
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.api.questioning_api import api_start_clarification_session, api_regenerate_summary
+from src.api.workflow_api import api_get_session_trace
+from src.utils import providers
+from src.utils.logger import log_context
+from src.utils.providers import ModelError, ModelErrorKind, chat_completion, get_provider_settings
+
+
+class SessionTraceTest(unittest.TestCase):
+
+    def setUp(self):
+        self._settings = (app_config.offline, app_config.default_model_provider, app_config.retry_delay,
+                          app_config.response_cache_enabled)
+        app_config.offline = False
+        app_config.response_cache_enabled = False
+
+    def tearDown(self):
+        (app_config.offline, app_config.default_model_provider, app_config.retry_delay,
+         app_config.response_cache_enabled) = self._settings
+
+    def test_agent_and_model_call_spans(self):
+        app_config.default_model_provider = "mock"
+        session_id = api_start_clarification_session({"raw_text": "A marketplace for local bakers"})["session_id"]
+        api_regenerate_summary(session_id)
+
+        data = api_get_session_trace(session_id)["data"]
+        agents = [span["name"] for span in data["spans"] if span["kind"] == "agent"]
+        self.assertEqual(agents, ["clarifier", "summarizer"])
+        calls = [span for span in data["spans"] if span["kind"] == "model_call"]
+        self.assertEqual([(span["agent"], span["provider"]) for span in calls], [("clarifier", "mock"), ("summarizer", "mock")])
+        self.assertEqual(data["totals"]["model_calls"], 2)
+        self.assertLessEqual(data["spans"][0]["started_at"], data["spans"][-1]["started_at"])
+
+    def test_retries_and_token_usage(self):
+        app_config.retry_delay = 0.0
+        response = {"choices": [{"message": {"content": "{}"}}], "usage": {"prompt_tokens": 12, "completion_tokens": 3}}
+        failures = [ModelError(ModelErrorKind.UNAVAILABLE, "502", "openai")]
+
+        def post_once(*args, **kwargs):
+            if failures:
+                raise failures.pop()
+            return response
+
+        with mock.patch.dict(os.environ, {"OPENAI_API_KEY": "sk-test-key-0000000000"}), \
+                mock.patch.object(providers, "_post_once", post_once):
+            settings = get_provider_settings("openai")
+            with log_context(session_id="trace_retry_session", agent="clarifier"):
+                chat_completion(settings, "system", "user", max_retries=2)
+
+        data = api_get_session_trace("trace_retry_session")["data"]
+        calls = [span for span in data["spans"] if span["kind"] == "model_call"]
+        self.assertEqual([(span["attempt"], span["success"]) for span in calls], [(0, False), (1, True)])
+        self.assertEqual(calls[0]["error_code"], "unavailable")
+        self.assertEqual(data["totals"]["retries"], 1)
+        self.assertEqual(data["totals"]["failed_calls"], 1)
+        self.assertEqual((data["totals"]["input_tokens"], data["totals"]["output_tokens"]), (12, 3))
+
+    def test_unknown_session(self):
+        self.assertEqual(api_get_session_trace("clar_missing")["error_info"]["code"], "not_found")
+
+
+if __name__ == "__main__":
+    unittest.main()
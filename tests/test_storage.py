@@ -0,0 +1,64 @@
+"""会话与项目存储：ID 校验，防止拼接出数据目录之外的路径"""
+
+import json
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.utils.storage import CacheStorage, SessionStorage, is_valid_storage_id, new_session_id, storage_file
+from src.api.questioning_api import api_delete_session
+
+
+class StorageIdTest(unittest.TestCase):
+
+    def test_valid_ids(self):
+        self.assertTrue(is_valid_storage_id(new_session_id()))
+        self.assertTrue(is_valid_storage_id("3f2b8c1e-5d6a-4b7c-9e0f-1a2b3c4d5e6f"))
+
+    def test_rejects_paths(self):
+        for value in ("../projects/p1", "a/b", "..", "", ".hidden", "a\\b", None, 42):
+            self.assertFalse(is_valid_storage_id(value), value)
+        with self.assertRaises(ValueError):
+            storage_file(app_config.projects_dir, "../x")
+
+    def test_delete_session_cannot_escape_sessions_dir(self):
+        app_config.projects_dir.mkdir(parents=True, exist_ok=True)
+        victim = app_config.projects_dir / "victim.json"
+        victim.write_text(json.dumps({"id": "victim"}), encoding="utf-8")
+        storage = SessionStorage()
+        relative = "../" + victim.relative_to(app_config.data_dir).with_suffix("").as_posix()
+
+        self.assertIsNone(storage.load_session(relative))
+        self.assertFalse(storage.delete_session(relative))
+        result = api_delete_session(relative)
+        self.assertFalse(result["success"])
+        self.assertEqual(result["error_info"]["code"], "not_found")
+        self.assertTrue(victim.exists())
+
+
+class CacheSizeLimitTest(unittest.TestCase):
+
+    def setUp(self):
+        self.cache = CacheStorage()
+        self.cache.clear()
+
+    def test_set_scans_only_when_over_limit(self):
+        with mock.patch.object(app_config, "cache_max_size_mb", 1.0), \
+                mock.patch.object(CacheStorage, "enforce_size_limit", autospec=True) as enforce:
+            for index in range(20):
+                self.cache.set(f"small-{index}", "x" * 100)
+            enforce.assert_not_called()
+
+    def test_oldest_entries_evicted_over_limit(self):
+        with mock.patch.object(app_config, "cache_max_size_mb", 0.01):
+            for index in range(10):
+                self.cache.set(f"entry-{index}", "x" * 2000)
+            self.assertLessEqual(self.cache.stats()["total_bytes"], 10 * 1024 * 1.05)
+            self.assertIsNotNone(self.cache.get("entry-9"))
+            self.assertIsNone(self.cache.get("entry-0"))
+
+
+if __name__ == "__main__":
+    unittest.main()
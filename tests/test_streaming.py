@@ -0,0 +1,117 @@
+"""流式输出：解析各提供商的 SSE 响应，并以 agent.output_chunk 事件推送"""
+
+import json
+import os
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.api import questioning_api
+from src.utils import providers
+from src.utils.event_bus import EventTypes, event_bus
+from src.utils.providers import ModelError, chat_completion_stream, get_provider_settings
+
+
+class FakeStream:
+
+    def __init__(self, events, status_code=200, text=""):
+        self.status_code = status_code
+        self.text = text
+        self.headers = {}
+        self.lines = [f"data: {json.dumps(event)}" if isinstance(event, dict) else event for event in events]
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        return False
+
+    def iter_lines(self, decode_unicode=False):
+        for line in self.lines:
+            yield line
+            yield ""
+
+
+class StreamingTest(unittest.TestCase):
+
+    def setUp(self):
+        for name, value in (("offline", False), ("response_cache_enabled", False), ("retry_delay", 0.0)):
+            patcher = mock.patch.object(app_config, name, value)
+            patcher.start()
+            self.addCleanup(patcher.stop)
+        env = mock.patch.dict(os.environ, {
+            "OPENAI_API_KEY": "sk-test-key-0000000000", "ANTHROPIC_API_KEY": "sk-ant-test-key-0000000000",
+        })
+        env.start()
+        self.addCleanup(env.stop)
+
+    def stream(self, provider, response, **kwargs):
+        chunks = []
+        with mock.patch.object(providers.requests, "post", return_value=response) as post:
+            text = chat_completion_stream(get_provider_settings(provider), "Return JSON.", "idea", chunks.append, **kwargs)
+        return text, chunks, post
+
+    def test_openai_stream(self):
+        response = FakeStream([
+            {"choices": [{"delta": {"role": "assistant"}}]},
+            {"choices": [{"delta": {"content": '{"a": '}}]},
+            {"choices": [{"delta": {"content": "1}"}}]},
+            {"choices": [], "usage": {"prompt_tokens": 9, "completion_tokens": 4}},
+            "data: [DONE]",
+        ])
+        text, chunks, post = self.stream("openai", response)
+        self.assertEqual(text, '{"a": 1}')
+        self.assertEqual(chunks, ['{"a": ', "1}"])
+        self.assertTrue(post.call_args.kwargs["json"]["stream"])
+        self.assertTrue(post.call_args.kwargs["stream"])
+
+    def test_anthropic_stream_with_json_prefill(self):
+        response = FakeStream([
+            {"type": "message_start", "message": {"usage": {"input_tokens": 5}}},
+            {"type": "content_block_delta", "delta": {"type": "text_delta", "text": '"a": 1}'}},
+            {"type": "message_delta", "usage": {"output_tokens": 3}},
+            {"type": "message_stop"},
+        ])
+        text, chunks, _ = self.stream("anthropic", response, json_mode=True)
+        self.assertEqual(text, '{"a": 1}')
+        self.assertEqual(chunks[0], "{")
+
+    def test_errors(self):
+        with self.assertRaises(ModelError) as ctx:
+            self.stream("openai", FakeStream([], status_code=401, text="invalid key"))
+        self.assertEqual(ctx.exception.kind.value, "invalid_key")
+        with self.assertRaises(ModelError):
+            self.stream("anthropic", FakeStream([{"type": "error", "error": {"message": "overloaded"}}]))
+
+    def test_agent_chat_emits_chunks_and_falls_back(self):
+        events = []
+        event_bus.subscribe(EventTypes.AGENT_OUTPUT_CHUNK, events.append)
+        self.addCleanup(event_bus.unsubscribe, EventTypes.AGENT_OUTPUT_CHUNK, events.append)
+        settings = get_provider_settings("openai")
+        request = {"json_mode": True}
+
+        def stream_ok(settings, system, user, on_chunk, **kwargs):
+            on_chunk('{"questions": ')
+            on_chunk("[]}")
+            return '{"questions": []}'
+
+        with mock.patch.object(questioning_api, "chat_completion_stream", stream_ok):
+            content = questioning_api._stream_agent_chat("clarifier", settings, "s", "u", request)
+        self.assertEqual(content, '{"questions": []}')
+        self.assertEqual([event.data["delta"] for event in events], ['{"questions": ', "[]}", ""])
+        self.assertTrue(events[-1].data["done"])
+
+        events.clear()
+        failure = ModelError(providers.ModelErrorKind.UNAVAILABLE, "boom", "openai")
+        with mock.patch.object(questioning_api, "chat_completion_stream", side_effect=failure), \
+                mock.patch.object(questioning_api, "chat_completion", return_value="{}") as chat:
+            content = questioning_api._stream_agent_chat("clarifier", settings, "s", "u", request)
+        self.assertEqual(content, "{}")
+        chat.assert_called_once()
+        self.assertEqual([event.data["delta"] for event in events], ["{}", ""])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,78 @@
+"""总结编辑：字段校验与保存为新版本"""
+
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from src.api.questioning_api import (
+    ClarificationSessionManager,
+    _validate_summary_edit,
+    api_regenerate_summary,
+    api_start_clarification_session,
+    api_update_summary,
+)
+
+
+class ValidateSummaryEditTest(unittest.TestCase):
+
+    def test_cleans_values(self):
+        cleaned = _validate_summary_edit({
+            "title": "  Bakery marketplace ",
+            "constraints": [" no delivery ", "", "  "],
+            "domain_fields": {"pricing": " subscription "},
+        })
+        self.assertEqual(cleaned, {
+            "title": "Bakery marketplace",
+            "constraints": ["no delivery"],
+            "domain_fields": {"pricing": "subscription"},
+        })
+
+    def test_rejects_invalid_changes(self):
+        cases = [
+            (None, "non-empty object"),
+            ({}, "non-empty object"),
+            ({"title": 3}, "title"),
+            ({"risks": "one risk"}, "risks"),
+            ({"risks": ["ok", 2]}, "risks"),
+            ({"domain_fields": {"pricing": 9}}, "domain_fields"),
+            ({"owner": "me"}, "unknown summary field: owner"),
+        ]
+        for changes, message in cases:
+            with self.assertRaises(ValueError, msg=changes) as ctx:
+                _validate_summary_edit(changes)
+            self.assertIn(message, str(ctx.exception))
+
+
+class UpdateSummaryTest(unittest.TestCase):
+
+    def test_update_creates_version(self):
+        session_id = api_start_clarification_session({"raw_text": "A marketplace for local bakers"})["session_id"]
+        self.assertEqual(api_update_summary(session_id, {"title": "x"})["error_info"]["code"], "invalid_state")
+        api_regenerate_summary(session_id)
+
+        result = api_update_summary(session_id, {"constraints": ["Launch in one city"]})
+        self.assertTrue(result["success"], result)
+        self.assertEqual(result["data"]["updated"], ["constraints"])
+        self.assertEqual(result["data"]["summary"]["constraints"], ["Launch in one city"])
+
+        result = api_update_summary(session_id, {"title": "", "refined_idea": ""})
+        self.assertEqual(result["error_info"]["code"], "invalid_input")
+        self.assertEqual(api_update_summary("missing", {"title": "x"})["error_info"]["code"], "not_found")
+
+    def test_invalid_changes_rejected_before_loading(self):
+        with mock.patch.object(ClarificationSessionManager, "load") as load:
+            result = api_update_summary("missing", {"bogus": "x"})
+        self.assertEqual(result["error_info"]["code"], "invalid_input")
+        load.assert_not_called()
+
+    def test_storage_failure_is_not_invalid_input(self):
+        session_id = api_start_clarification_session({"raw_text": "A marketplace for local bakers"})["session_id"]
+        api_regenerate_summary(session_id)
+        with mock.patch.object(ClarificationSessionManager, "save", side_effect=OSError("disk full")):
+            result = api_update_summary(session_id, {"title": "Bakery marketplace"})
+        self.assertEqual(result["error_info"]["code"], "storage_error")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,83 @@
+"""工作流任务队列：排队位置与暂停 / 恢复的状态转换"""
+
+import time
+import unittest
+from unittest import mock
+
+import support  # noqa: F401
+
+from config.app_config import app_config
+from src.api import workflow_api
+from src.api.workflow_api import (
+    api_start_workflow, api_pause_workflow, api_resume_workflow, api_stop_workflow, api_get_workflow_status,
+    api_list_running_jobs,
+)
+
+
+def wait_for_status(session_id, status, timeout=10.0):
+    deadline = time.monotonic() + timeout
+    while time.monotonic() < deadline:
+        if api_get_workflow_status(session_id)["data"]["status"] == status:
+            return True
+        time.sleep(0.05)
+    return False
+
+
+class WorkflowQueueTest(unittest.TestCase):
+
+    @classmethod
+    def setUpClass(cls):
+        app_config.max_concurrent_workflows = 1
+
+    def test_queued_workflow_cannot_be_paused_and_still_runs(self):
+        first = api_start_workflow("project_a", "idea a")
+        first_id = first["session_id"]
+        self.assertIsNone(first["data"]["queue_position"])
+        self.assertTrue(wait_for_status(first_id, "running"))
+
+        second = api_start_workflow("project_b", "idea b")
+        second_id = second["session_id"]
+        self.assertEqual(second["data"]["queue_position"], 0)
+
+        paused = api_pause_workflow(second_id)
+        self.assertFalse(paused["success"])
+        self.assertEqual(paused["error_info"]["code"], "invalid_state")
+        resumed = api_resume_workflow(second_id)
+        self.assertEqual(resumed["error_info"]["code"], "invalid_state")
+        self.assertEqual(api_get_workflow_status(second_id)["data"]["status"], "queued")
+
+        # 前一个任务结束后，排队的任务仍会被执行
+        api_stop_workflow(first_id)
+        self.assertTrue(wait_for_status(second_id, "running"))
+        self.assertIsNone(api_get_workflow_status(second_id)["data"]["queue_position"])
+        self.assertTrue(api_pause_workflow(second_id)["success"])
+        api_stop_workflow(second_id)
+
+    def test_stopping_queued_job_moves_later_jobs_up(self):
+        running_id = api_start_workflow("project_r", "idea r")["session_id"]
+        self.assertTrue(wait_for_status(running_id, "running"))
+        queued_ids = [api_start_workflow(f"project_{i}", f"idea {i}")["session_id"] for i in range(3)]
+        self.assertEqual([api_get_workflow_status(i)["data"]["queue_position"] for i in queued_ids], [0, 1, 2])
+
+        self.assertTrue(api_stop_workflow(queued_ids[1])["success"])
+        self.assertNotIn(queued_ids[1], workflow_api._workflow_queue)
+        self.assertEqual(api_get_workflow_status(queued_ids[1])["data"]["status"], "stopped")
+        self.assertEqual(api_get_workflow_status(queued_ids[2])["data"]["queue_position"], 1)
+        jobs = {job["session_id"]: job for job in api_list_running_jobs()["data"]["jobs"]}
+        self.assertNotIn(queued_ids[1], jobs)
+        self.assertEqual(jobs[queued_ids[2]]["queue_position"], 1)
+
+        for session_id in [running_id, queued_ids[0], queued_ids[2]]:
+            api_stop_workflow(session_id)
+
+    def test_waiting_ahead_counts_idle_workers(self):
+        with mock.patch.object(app_config, "max_concurrent_workflows", 3):
+            with mock.patch.object(workflow_api, "_running_workflows", 1):
+                # 两个空闲线程：队列前两个任务立即开始
+                self.assertEqual([workflow_api._waiting_ahead(i) for i in range(4)], [None, None, 0, 1])
+            with mock.patch.object(workflow_api, "_running_workflows", 3):
+                self.assertEqual([workflow_api._waiting_ahead(i) for i in range(2)], [0, 1])
+
+
+if __name__ == "__main__":
+    unittest.main()